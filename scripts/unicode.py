@@ -12,8 +12,22 @@
 
 # This script uses the following Unicode security tables:
 # - IdentifierStatus.txt
+# - IdentifierType.txt
+# - confusables.txt
 # - ReadMe.txt
 #
+# And the following Unicode Character Database files:
+# - Scripts.txt
+# - PropertyValueAliases.txt
+# - UnicodeData.txt
+# - PropList.txt
+# - DerivedCoreProperties.txt
+# - extracted/DerivedJoiningType.txt
+# - ScriptExtensions.txt, of SCRIPT_EXTENSIONS_VERSION
+#
+# And the IDNA mapping table:
+# - IdnaMappingTable.txt
+#
 # Since this should not require frequent updates, we just store this
 # out-of-line and check the unicode.rs file into git.
 
@@ -32,6 +46,7 @@ preamble = '''// Copyright 2012-2015 The Rust Project Developers. See the COPYRI
 // NOTE: The following code was generated by "scripts/unicode.py", do not edit directly
 
 #![allow(missing_docs, non_upper_case_globals, non_snake_case)]
+#![allow(clippy::match_single_binding, clippy::redundant_static_lifetimes)]
 '''
 
 UNICODE_VERSION = (13, 0, 0)
@@ -56,9 +71,21 @@ def fetch_unidata(f):
         sys.stderr.write("cannot load %s" % f)
         exit(1)
 
+def fetch_idna(f):
+    if not os.path.exists(os.path.basename(f)):
+        os.system("curl -O http://www.unicode.org/Public/idna/%s/%s"
+                  % (UNICODE_VERSION_NUMBER, f))
+
+    if not os.path.exists(os.path.basename(f)):
+        sys.stderr.write("cannot load %s\n" % f)
+        exit(1)
+
 # Implementation from unicode-segmentation
-def load_properties(f, interestingprops = None):
-    fetch(f)
+def load_properties(f, interestingprops = None, unidata = False):
+    if unidata:
+        fetch_unidata(f)
+    else:
+        fetch(f)
     props = {}
     re1 = re.compile(r"^ *([0-9A-F]+) *; *(\w+)")
     re2 = re.compile(r"^ *([0-9A-F]+)\.\.([0-9A-F]+) *; *(\w+)")
@@ -90,6 +117,24 @@ def load_properties(f, interestingprops = None):
 
     return props
 
+def load_identifier_types(f):
+    # Unlike most property files, IdentifierType.txt may assign several
+    # space-separated values to the same code points.
+    fetch(f)
+    types = []
+    re1 = re.compile(r"^ *([0-9A-F]+)(?:\.\.([0-9A-F]+))? *; *([\w ]+?) *(?:#|$)")
+
+    for line in fileinput.input(os.path.basename(f), openhook=fileinput.hook_encoded("utf-8")):
+        m = re1.match(line)
+        if not m:
+            continue
+        d_lo = int(m.group(1), 16)
+        d_hi = int(m.group(2) or m.group(1), 16)
+        for ty in m.group(3).split():
+            types.append((d_lo, d_hi, ty))
+
+    return types
+
 def load_script_properties(f, interestingprops):
     fetch_unidata(f)
     props = {}
@@ -189,6 +234,54 @@ def load_scripts(f):
     script_table.sort(key=lambda w: w[0])
     return (longforms, script_table)
 
+def load_unicode_data(f):
+    fetch_unidata(f)
+    udata = {}
+    range_start = None
+
+    for line in fileinput.input(os.path.basename(f)):
+        fields = line.strip().split(";")
+        if len(fields) != 15:
+            continue
+        cp = int(fields[0], 16)
+        # Large blocks of similar code points are given as a First/Last pair of lines
+        if fields[1].endswith(", First>"):
+            range_start = cp
+            continue
+        if fields[1].endswith(", Last>"):
+            for c in range(range_start, cp + 1):
+                udata[c] = fields
+            range_start = None
+            continue
+        udata[cp] = fields
+
+    return udata
+
+def group_unicode_data(udata, field):
+    # Group the code points by the value of the given UnicodeData.txt field,
+    # collapsing runs of consecutive code points into ranges.
+    props = {}
+    for cp in sorted(udata):
+        prop = udata[cp][field]
+        if prop not in props:
+            props[prop] = []
+        ranges = props[prop]
+        if ranges and ranges[-1][1] == cp - 1:
+            ranges[-1] = (ranges[-1][0], cp)
+        else:
+            ranges.append((cp, cp))
+    return props
+
+def merge_ranges(ranges):
+    # Merge sorted ranges which touch each other
+    merged = []
+    for (lo, hi) in ranges:
+        if merged and merged[-1][1] + 1 >= lo:
+            merged[-1] = (merged[-1][0], max(merged[-1][1], hi))
+        else:
+            merged.append((lo, hi))
+    return merged
+
 def is_script_ignored_in_mixedscript(source):
     return source == 'Zinh' or source == 'Zyyy' or source == 'Zzzz'
 
@@ -465,6 +558,60 @@ def emit_table(f, name, t_data, t_type = "&'static [(char, char)]", is_pub=True,
     format_table_content(f, data, 8)
     f.write("\n    ];\n\n")
 
+# Code points are looked up in a CharTrie by chunks of 1 << TRIE_CHUNK_BITS,
+# then by blocks of 64, each block being a bitmap shared by all identical blocks.
+TRIE_CHUNK_BITS = 12
+
+def emit_char_trie(f, name, points):
+    blocks_per_chunk = 1 << (TRIE_CHUNK_BITS - 6)
+    ascii = 0
+    for c in points:
+        if c < 0x80:
+            ascii |= 1 << c
+    words = [0] * (0x110000 >> 6)
+    for c in points:
+        words[c >> 6] |= 1 << (c & 0x3f)
+
+    leaves = [0]
+    leaf_ids = {0: 0}
+    block_tables = []
+    block_table_ids = {}
+    chunks = []
+    rank = 0
+    for chunk in range(0x110000 >> TRIE_CHUNK_BITS):
+        table = []
+        chunk_rank = 0
+        for word in words[chunk * blocks_per_chunk:(chunk + 1) * blocks_per_chunk]:
+            if word not in leaf_ids:
+                leaf_ids[word] = len(leaves)
+                leaves.append(word)
+            table.append((leaf_ids[word], chunk_rank))
+            chunk_rank += bin(word).count("1")
+        table = tuple(table)
+        if table not in block_table_ids:
+            block_table_ids[table] = len(block_tables)
+            block_tables.append(table)
+        chunks.append((block_table_ids[table], rank))
+        rank += chunk_rank
+    if len(leaves) > 0x10000 or len(block_tables) > 0x10000:
+        raise Exception("too many blocks in %s" % name)
+
+    f.write("    const %s: &'static super::util::CharTrie = &super::util::CharTrie {\n" % name)
+    f.write("        ascii: 0x%x,\n" % ascii)
+    f.write("        chunks: &[\n")
+    format_table_content(f, ",".join("(%d,%d)" % x for x in chunks), 12)
+    f.write("\n        ],\n")
+    f.write("        blocks: &[\n")
+    for table in block_tables:
+        f.write("            [\n")
+        format_table_content(f, ",".join("(%d,%d)" % x for x in table), 16)
+        f.write("\n            ],\n")
+    f.write("        ],\n")
+    f.write("        leaves: &[\n")
+    format_table_content(f, ",".join("0x%x" % x for x in leaves), 12)
+    f.write("\n        ],\n")
+    f.write("    };\n\n")
+
 def emit_identifier_module(f):
     f.write("pub mod identifier {")
     f.write("""
@@ -491,51 +638,119 @@ def emit_identifier_module(f):
     }
     #[inline]
     pub fn identifier_status_allowed(c: char) -> bool {
-        // FIXME: do we want to special case ASCII here?
-        match c as usize {
-            _ => super::util::bsearch_range_table(c, IDENTIFIER_STATUS)
-        }
+        IDENTIFIER_STATUS_TRIE.contains(c)
     }
 
     #[inline]
     pub fn identifier_type(c: char) -> Option<IdentifierType> {
-        // FIXME: do we want to special case ASCII here?
-        match c as usize {
-            _ => super::util::bsearch_range_value_table(c, IDENTIFIER_TYPE)
-        }
+        identifier_types(c).first().map(|&(_, _, ty)| ty)
     }
+
+    #[inline]
+    pub fn identifier_types(c: char) -> &'static [(char, char, IdentifierType)] {
+        // A range with several identifier types is listed once for each of them,
+        // in the order they are given in IdentifierType.txt. Every code point is
+        // in a slice of the table, possibly an empty one between two ranges.
+        let (start, len) = IDENTIFIER_TYPE_SLICES[IDENTIFIER_TYPE_STARTS.rank(c) - 1];
+        &IDENTIFIER_TYPE[start as usize..start as usize + len as usize]
+    }
+
+    #[cfg(any(test, feature = "bench"))]
+    pub fn identifier_status_allowed_bsearch(c: char) -> bool {
+        super::util::bsearch_range_table(c, IDENTIFIER_STATUS)
+    }
+
+    #[cfg(any(test, feature = "bench"))]
+    pub fn identifier_types_bsearch(c: char) -> &'static [(char, char, IdentifierType)] {
+        super::util::bsearch_range_value_table_all(c, IDENTIFIER_TYPE)
+    }
+
 """)
 
     f.write("    // Identifier status table:\n")
     identifier_status_table = load_properties("IdentifierStatus.txt")
+    f.write("    #[cfg(any(test, feature = \"bench\"))]\n")
     emit_table(f, "IDENTIFIER_STATUS", identifier_status_table['Allowed'], "&'static [(char, char)]", is_pub=False,
             pfun=lambda x: "(%s,%s)" % (escape_char(x[0]), escape_char(x[1])))
-    identifier_type = load_properties("IdentifierType.txt")
-    type_table = []
-    for ty in identifier_type:
-        type_table.extend([(x, y, ty) for (x, y) in identifier_type[ty]])
-    
+    allowed = set()
+    for (lo, hi) in identifier_status_table['Allowed']:
+        allowed.update(range(lo, hi + 1))
+    emit_char_trie(f, "IDENTIFIER_STATUS_TRIE", allowed)
+
+    type_table = load_identifier_types("IdentifierType.txt")
+    # Python's sort is stable, so multiple types of the same range keep their order
     type_table.sort(key=lambda w: w[0])
 
     emit_table(f, "IDENTIFIER_TYPE", type_table, "&'static [(char, char, IdentifierType)]", is_pub=False,
             pfun=lambda x: "(%s,%s, IdentifierType::%s)" % (escape_char(x[0]), escape_char(x[1]), x[2]))
+
+    # Split the code points into slices of the table: one for each distinct range,
+    # and an empty one for each gap between them.
+    starts = [0]
+    slices = [(0, 0)]
+    idx = 0
+    while idx < len(type_table):
+        (lo, hi, _) = type_table[idx]
+        end = idx
+        while end < len(type_table) and type_table[end][0] == lo:
+            if type_table[end][1] != hi:
+                raise Exception("overlapping ranges in identifier type table: %x" % lo)
+            end += 1
+        if lo < starts[-1]:
+            raise Exception("overlapping ranges in identifier type table: %x" % lo)
+        if lo == starts[-1]:
+            slices[-1] = (idx, end - idx)
+        else:
+            starts.append(lo)
+            slices.append((idx, end - idx))
+        if hi < 0x10FFFF:
+            starts.append(hi + 1)
+            slices.append((0, 0))
+        idx = end
+    f.write("    // Slices of the identifier type table, for each code point in IDENTIFIER_TYPE_STARTS\n")
+    f.write("    // and the code points up to the next one:\n")
+    emit_table(f, "IDENTIFIER_TYPE_SLICES", slices, "&'static [(u16, u8)]", is_pub=False,
+            pfun=lambda x: "(%d,%d)" % x)
+    emit_char_trie(f, "IDENTIFIER_TYPE_STARTS", set(starts))
     f.write("}\n\n")
 
+def emit_string_pool(f, name, strings):
+    # All the strings are concatenated into one string literal, split over several lines
+    f.write("    const %s: &'static str = \"\\\n" % name)
+    line = ""
+    for c in "".join(strings):
+        esc = "\\u{%x}" % ord(c)
+        if len(line) + len(esc) >= 90:
+            f.write("        %s\\\n" % line)
+            line = ""
+        line += esc
+    f.write("        %s\";\n\n" % line)
+
 def emit_confusable_detection_module(f):
     f.write("pub mod confusable_detection {")
     f.write("""
 
     #[inline]
-    pub fn char_confusable_prototype(c: char) -> Option<&'static [char]> {
-        // FIXME: do we want to special case ASCII here?
-        match c as usize {
-            _ => super::util::bsearch_value_table(c, CONFUSABLES)
-        }
+    pub fn char_confusable_prototype(c: char) -> Option<&'static str> {
+        let idx = CONFUSABLES_TRIE.index(c)?;
+        Some(prototype(CONFUSABLES[idx]))
+    }
+
+    #[cfg(any(test, feature = "bench"))]
+    pub fn char_confusable_prototype_bsearch(c: char) -> Option<&'static str> {
+        let idx = CONFUSABLE_KEYS.binary_search(&c).ok()?;
+        Some(prototype(CONFUSABLES[idx]))
+    }
+
+    #[inline]
+    fn prototype(id: u16) -> &'static str {
+        let start = PROTOTYPE_OFFSETS[id as usize] as usize;
+        let end = PROTOTYPE_OFFSETS[id as usize + 1] as usize;
+        &PROTOTYPES[start..end]
     }
 
 """)
 
-    f.write("    // Confusable table:\n")
     confusable_table = load_confusables("confusables.txt")
     confusable_table.sort(key=lambda w: w[0])
     
@@ -545,13 +760,129 @@ def emit_confusable_detection_module(f):
             raise Exception("duplicate keys in confusables table: %s" % k)
         last_key = k
 
-    emit_table(f, "CONFUSABLES", confusable_table, "&'static [(char, &'static [char])]", is_pub=False,
-            pfun=lambda x: "(%s, &%s)" % (escape_char(x[0]), escape_char_list(x[1])))
+    prototypes = []
+    prototype_ids = {}
+    offsets = [0]
+    for (_, v) in confusable_table:
+        proto = "".join(chr(c) for c in v)
+        if proto not in prototype_ids:
+            prototype_ids[proto] = len(prototypes)
+            prototypes.append(proto)
+            offsets.append(offsets[-1] + len(proto.encode("utf-8")))
+    if offsets[-1] > 0xffff or len(prototypes) > 0xffff:
+        raise Exception("confusable prototypes do not fit in u16 offsets")
+
+    f.write("    // Confusable table, with the ID of the prototype of each character:\n")
+    f.write("    #[cfg(any(test, feature = \"bench\"))]\n")
+    emit_table(f, "CONFUSABLE_KEYS", [k for (k, _) in confusable_table], "&'static [char]", is_pub=False,
+            pfun=escape_char)
+    emit_table(f, "CONFUSABLES", confusable_table, "&'static [u16]", is_pub=False,
+            pfun=lambda x: "%d" % prototype_ids["".join(chr(c) for c in x[1])])
+    emit_char_trie(f, "CONFUSABLES_TRIE", set(k for (k, _) in confusable_table))
+    f.write("    // Confusable prototypes, as byte ranges of PROTOTYPES given by consecutive offsets:\n")
+    emit_table(f, "PROTOTYPE_OFFSETS", offsets, "&'static [u16]", is_pub=False, pfun=lambda x: "%d" % x)
+    emit_string_pool(f, "PROTOTYPES", prototypes)
+    emit_reverse_confusables(f, confusable_table, prototypes, prototype_ids)
     f.write("}\n\n")
 
+def emit_reverse_confusables(f, confusable_table, prototypes, prototype_ids):
+    f.write("""    /// Find the ID of a prototype, given how it compares with the prototype of each ID
+    pub fn find_prototype(mut cmp: impl FnMut(&'static str) -> core::cmp::Ordering) -> Option<u16> {
+        PROTOTYPES_SORTED.binary_search_by(|&id| cmp(prototype(id))).ok().map(|idx| PROTOTYPES_SORTED[idx])
+    }
+
+    #[inline]
+    pub fn char_confusable_prototype_id(c: char) -> Option<u16> {
+        CONFUSABLES_TRIE.index(c).map(|idx| CONFUSABLES[idx])
+    }
+
+    #[inline]
+    pub fn prototype_str(id: u16) -> &'static str {
+        prototype(id)
+    }
+
+    /// The characters with the given prototype, in code point order
+    #[inline]
+    pub fn prototype_sources(id: u16) -> &'static str {
+        let start = PROTOTYPE_SOURCE_OFFSETS[id as usize] as usize;
+        let end = PROTOTYPE_SOURCE_OFFSETS[id as usize + 1] as usize;
+        &PROTOTYPE_SOURCES[start..end]
+    }
+
+""")
+    order = sorted(range(len(prototypes)), key=lambda id: [ord(c) for c in prototypes[id]])
+    f.write("    // Prototype IDs, sorted by prototype:\n")
+    emit_table(f, "PROTOTYPES_SORTED", order, "&'static [u16]", is_pub=False, pfun=lambda x: "%d" % x)
+
+    sources = [""] * len(prototypes)
+    for (k, v) in confusable_table:
+        sources[prototype_ids["".join(chr(c) for c in v)]] += chr(k)
+    offsets = [0]
+    for group in sources:
+        offsets.append(offsets[-1] + len(group.encode("utf-8")))
+    if offsets[-1] > 0xffff:
+        raise Exception("confusable sources do not fit in u16 offsets")
+    f.write("    // Characters with each prototype, as byte ranges of PROTOTYPE_SOURCES given by consecutive offsets:\n")
+    emit_table(f, "PROTOTYPE_SOURCE_OFFSETS", offsets, "&'static [u16]", is_pub=False, pfun=lambda x: "%d" % x)
+    emit_string_pool(f, "PROTOTYPE_SOURCES", sources)
+
 def escape_script_constant(name, longforms):
     return "Script::" + longforms[name].strip()
 
+# Scripts of Scripts.txt that the `Script` enum of the unicode-script dependency
+# does not have yet, since it is based on an older version of Unicode
+MISSING_SCRIPTS = ["Chorasmian", "Dives_Akuru", "Khitan_Small_Script", "Yezidi"]
+
+# The version of ScriptExtensions.txt the `ScriptExtension` enum of the unicode-script
+# dependency is generated from, which names its multi-script values
+SCRIPT_EXTENSIONS_VERSION = "12.0.0"
+
+def load_script_extensions():
+    # Loads the multi-script values of ScriptExtensions.txt, as sorted lists of ISO 15924 codes
+    f = "ScriptExtensions.txt"
+    if not os.path.exists(f):
+        os.system("curl -O http://www.unicode.org/Public/%s/ucd/%s"
+                  % (SCRIPT_EXTENSIONS_VERSION, f))
+    if not os.path.exists(f):
+        sys.stderr.write("cannot load %s\n" % f)
+        exit(1)
+    extensions = []
+    re1 = re.compile(r"^ *[0-9A-F]+(?:\.\.[0-9A-F]+)? *; *([\w ]+?) *(?:#|$)")
+    for line in fileinput.input(f):
+        m = re1.match(line)
+        if m:
+            ext = sorted(m.group(1).split())
+            if len(ext) > 1 and ext not in extensions:
+                extensions.append(ext)
+    return sorted(extensions)
+
+def emit_script_list_module(f):
+    (longforms, shortforms) = aliases()
+    scripts = load_script_properties("Scripts.txt", [])
+    script_list = sorted(shortforms[script] for script in scripts
+                         if script not in ["Common", "Unknown", "Inherited"] + MISSING_SCRIPTS)
+    f.write("pub mod script_list {\n")
+    f.write("    use unicode_script::{Script, ScriptExtension};\n\n")
+    f.write("    // Every script except Common, Inherited and Unknown, sorted by ISO 15924 code:\n")
+    emit_table(f, "SCRIPTS", script_list, "&'static [Script]",
+            pfun=lambda x: escape_script_constant(x, longforms))
+    f.write("""
+    // The scripts of the multi-script values of ScriptExtensions.txt %s, which the
+    // `ScriptExtension` enum is generated from; its other multi-script values are only
+    // intersections of these
+    pub fn extension_scripts(ext: ScriptExtension) -> Option<&'static [Script]> {
+        Some(match ext {
+""" % SCRIPT_EXTENSIONS_VERSION)
+    for ext in load_script_extensions():
+        f.write("            ScriptExtension::%s => &[%s],\n"
+                % ("".join(ext), ", ".join(escape_script_constant(x, longforms) for x in ext)))
+    f.write("""            _ => return None,
+        })
+    }
+}
+
+""")
+
 def emit_potiential_mixed_script_confusable(f):
     f.write("pub mod potential_mixed_script_confusable {")
     f.write("""
@@ -581,6 +912,279 @@ def emit_potiential_mixed_script_confusable(f):
     f.write("}\n\n")
 
 
+def load_whole_script_confusables(f, identifier_allowed, scripts):
+    confusables = load_confusables(f)
+
+    # Group the code points into equivalence classes keyed by their prototype, the same way
+    # as `load_potential_mixedscript_confusables` does. Code points restricted from identifier
+    # usage never take part in a whole-script confusable, so we skip them up front.
+    classes = {}
+    for (d_source, d_proto_list) in confusables:
+        if not is_codepoint_identifier_allowed(d_source, identifier_allowed):
+            continue
+        d_protos = escape_char_list(d_proto_list)
+        if d_protos not in classes:
+            classes[d_protos] = (d_proto_list, [])
+            if len(d_proto_list) == 1 and is_codepoint_identifier_allowed(d_proto_list[0], identifier_allowed):
+                classes[d_protos][1].append(d_proto_list[0])
+        classes[d_protos][1].append(d_source)
+
+    whole_script_confusable = {}
+    for _, (proto_lst, source) in classes.items():
+        # The scripts each member of the equivalence class can be written in.
+        member_scripts = [codepoint_script(c, scripts) for c in source]
+        # A multiple code point prototype is a candidate too, as long as it is itself
+        # a single-script sequence of code points allowed in identifiers.
+        if len(proto_lst) > 1:
+            proto_allowed = all(is_codepoint_identifier_allowed(c, identifier_allowed) for c in proto_lst)
+            proto_scripts = [x for x in script_list(proto_lst, scripts) if not is_script_ignored_in_mixedscript(x)]
+            if proto_allowed and len(proto_scripts) == 1:
+                member_scripts.append(proto_scripts[0])
+        for item, script in zip(source, member_scripts):
+            if is_script_ignored_in_mixedscript(script):
+                continue
+            for target in member_scripts:
+                if is_script_ignored_in_mixedscript(target) or target == script:
+                    continue
+                targets = whole_script_confusable.setdefault(item, [])
+                if target not in targets:
+                    targets.append(target)
+    return whole_script_confusable
+
+def emit_whole_script_confusable(f):
+    f.write("pub mod whole_script_confusable {")
+    f.write("""
+    use unicode_script::Script;
+
+    #[inline]
+    pub fn whole_script_confusable_targets(c: char) -> &'static [Script] {
+        match c as usize {
+            _ => super::util::bsearch_value_table(c, CONFUSABLES).unwrap_or(&[])
+        }
+    }
+
+""")
+    identifier_status_table = load_properties("IdentifierStatus.txt")
+    longforms, scripts = load_scripts("Scripts.txt")
+    identifier_allowed = identifier_status_table['Allowed']
+    whole_script_confusable = load_whole_script_confusables("confusables.txt", identifier_allowed, scripts)
+    confusable_table = []
+    for source, targets in whole_script_confusable.items():
+        targets.sort()
+        confusable_table.append((source, targets))
+    confusable_table.sort(key=lambda w: w[0])
+    emit_table(f, "CONFUSABLES", confusable_table, "&'static [(char, &'static [Script])]", is_pub=False,
+            pfun=lambda x: "(%s, &[%s])" % (escape_char(x[0]), ", ".join(escape_script_constant(s, longforms) for s in x[1])))
+    f.write("}\n\n")
+
+
+def emit_decimal_digit_module(f):
+    f.write("pub mod decimal_digit {")
+    f.write("""
+
+    #[inline]
+    pub fn decimal_digit_zero_index(c: char) -> Option<usize> {
+        // Every set of decimal digits is a run of ten consecutive code points, starting with zero.
+        let idx = match DECIMAL_DIGIT_ZEROS.binary_search(&c) {
+            Ok(idx) => return Some(idx),
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+        if (c as u32) - (DECIMAL_DIGIT_ZEROS[idx] as u32) < 10 {
+            Some(idx)
+        } else {
+            None
+        }
+    }
+
+""")
+    udata = load_unicode_data("UnicodeData.txt")
+    zeros = [cp for cp in sorted(udata) if udata[cp][2] == "Nd" and udata[cp][6] == "0"]
+    for cp in sorted(udata):
+        if udata[cp][2] == "Nd" and not any(0 <= cp - z < 10 for z in zeros):
+            raise Exception("decimal digit not preceded by its zero: %s" % escape_char(cp))
+    # The set of zeros used by a string is stored as a bitset
+    if len(zeros) > 128:
+        raise Exception("too many sets of decimal digits for a u128 bitset")
+    f.write("    // Decimal digit zero table:\n")
+    emit_table(f, "DECIMAL_DIGIT_ZEROS", zeros, "&'static [char]",
+            pfun=lambda x: "%s" % escape_char(x))
+    f.write("}\n\n")
+
+def emit_nonspacing_mark_module(f):
+    f.write("pub mod nonspacing_mark {")
+    f.write("""
+
+    #[inline]
+    pub fn is_nonspacing_mark(c: char) -> bool {
+        match c as usize {
+            _ => super::util::bsearch_range_table(c, NONSPACING_MARK)
+        }
+    }
+
+    #[inline]
+    pub fn is_soft_dotted(c: char) -> bool {
+        match c as usize {
+            _ => super::util::bsearch_range_table(c, SOFT_DOTTED)
+        }
+    }
+
+""")
+    # UTS #39 counts both Mn and Me as nonspacing marks
+    gc = group_unicode_data(load_unicode_data("UnicodeData.txt"), 2)
+    nonspacing_marks = sorted(gc["Mn"] + gc["Me"])
+    f.write("    // Nonspacing mark table:\n")
+    emit_table(f, "NONSPACING_MARK", nonspacing_marks, "&'static [(char, char)]", is_pub=False)
+    soft_dotted = load_properties("PropList.txt", ["Soft_Dotted"], unidata=True)["Soft_Dotted"]
+    soft_dotted.sort()
+    f.write("    // Soft dotted table:\n")
+    emit_table(f, "SOFT_DOTTED", soft_dotted, "&'static [(char, char)]", is_pub=False)
+    f.write("}\n\n")
+
+def emit_xid_module(f):
+    f.write("pub mod xid {")
+    f.write("""
+
+    #[inline]
+    pub fn is_xid_start(c: char) -> bool {
+        match c as usize {
+            _ => super::util::bsearch_range_table(c, XID_START)
+        }
+    }
+
+    #[inline]
+    pub fn is_xid_continue(c: char) -> bool {
+        match c as usize {
+            _ => super::util::bsearch_range_table(c, XID_CONTINUE)
+        }
+    }
+
+    // The classes of characters used by the contexts in which UAX #31
+    // allows ZWJ and ZWNJ (rules A1, A2 and B)
+    #[inline]
+    pub fn is_letter(c: char) -> bool {
+        super::util::bsearch_range_table(c, LETTER)
+    }
+
+    #[inline]
+    pub fn is_mn(c: char) -> bool {
+        super::util::bsearch_range_table(c, MN)
+    }
+
+    #[inline]
+    pub fn is_left_joining(c: char) -> bool {
+        super::util::bsearch_range_table(c, LEFT_JOINING)
+    }
+
+    #[inline]
+    pub fn is_right_joining(c: char) -> bool {
+        super::util::bsearch_range_table(c, RIGHT_JOINING)
+    }
+
+    #[inline]
+    pub fn is_transparent_joining(c: char) -> bool {
+        super::util::bsearch_range_table(c, TRANSPARENT_JOINING)
+    }
+
+""")
+    xid = load_properties("DerivedCoreProperties.txt", ["XID_Start", "XID_Continue"], unidata=True)
+    f.write("    // XID_Start table:\n")
+    emit_table(f, "XID_START", sorted(xid["XID_Start"]), "&'static [(char, char)]", is_pub=False)
+    f.write("    // XID_Continue table:\n")
+    emit_table(f, "XID_CONTINUE", sorted(xid["XID_Continue"]), "&'static [(char, char)]", is_pub=False)
+
+    gc = group_unicode_data(load_unicode_data("UnicodeData.txt"), 2)
+    letters = sorted(gc["Lu"] + gc["Ll"] + gc["Lt"] + gc["Lm"] + gc["Lo"])
+    f.write("    // General_Category=Letter table:\n")
+    emit_table(f, "LETTER", merge_ranges(letters), "&'static [(char, char)]", is_pub=False)
+    f.write("    // General_Category=Nonspacing_Mark table:\n")
+    emit_table(f, "MN", sorted(gc["Mn"]), "&'static [(char, char)]", is_pub=False)
+
+    jt = load_properties("extracted/DerivedJoiningType.txt", ["L", "R", "D", "T"], unidata=True)
+    f.write("    // Joining_Type=Left_Joining or Dual_Joining table:\n")
+    emit_table(f, "LEFT_JOINING", merge_ranges(sorted(jt["L"] + jt["D"])), "&'static [(char, char)]", is_pub=False)
+    f.write("    // Joining_Type=Right_Joining or Dual_Joining table:\n")
+    emit_table(f, "RIGHT_JOINING", merge_ranges(sorted(jt["R"] + jt["D"])), "&'static [(char, char)]", is_pub=False)
+    f.write("    // Joining_Type=Transparent table:\n")
+    emit_table(f, "TRANSPARENT_JOINING", sorted(jt["T"]), "&'static [(char, char)]", is_pub=False)
+    f.write("}\n\n")
+
+def load_idna_mapping(f):
+    # Returns sorted (lo, hi, status, mapping) rows for hostnames, i.e. with
+    # nontransitional processing and UseSTD3ASCIIRules. The status is one of
+    # "Valid", "Ignored", "Mapped" and "Disallowed"; mapping is a list of code points.
+    fetch_idna(f)
+    rows = []
+    for line in fileinput.input(os.path.basename(f), openhook=fileinput.hook_encoded("utf-8")):
+        fields = [x.strip() for x in line.split("#")[0].split(";")]
+        if len(fields) < 2:
+            continue
+        if ".." in fields[0]:
+            lo, hi = [int(x, 16) for x in fields[0].split("..")]
+        else:
+            lo = hi = int(fields[0], 16)
+        status = fields[1]
+        mapping = []
+        if status in ("valid", "deviation"):
+            status = "Valid"
+        elif status == "ignored":
+            status = "Ignored"
+        elif status == "mapped":
+            status = "Mapped"
+            mapping = [int(x, 16) for x in fields[2].split()]
+        elif status in ("disallowed", "disallowed_STD3_valid", "disallowed_STD3_mapped"):
+            status = "Disallowed"
+        else:
+            raise Exception("unknown IDNA status: %s" % status)
+        if rows and status != "Mapped" and rows[-1][2] == status and rows[-1][1] + 1 == lo:
+            rows[-1] = (rows[-1][0], hi, status, mapping)
+        else:
+            rows.append((lo, hi, status, mapping))
+    return rows
+
+def emit_idna_mapping_module(f):
+    # Only the `domain` module uses this
+    f.write("#[cfg(feature = \"domain\")]\n")
+    f.write("pub mod idna_mapping {")
+    f.write("""
+
+    /// The UTS #46 mapping of a character, for nontransitional processing with UseSTD3ASCIIRules
+    #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+    pub enum IdnaMapping {
+        Valid,
+        Ignored,
+        Mapped(&'static str),
+        Disallowed,
+    }
+
+    #[inline]
+    pub fn idna_mapping(c: char) -> IdnaMapping {
+        use core::cmp::Ordering::{Equal, Less, Greater};
+        match IDNA_MAPPING.binary_search_by(|&(lo, hi, _)| {
+            if lo <= c && c <= hi { Equal }
+            else if hi < c { Less }
+            else { Greater }
+        }) {
+            Ok(idx) => IDNA_MAPPING[idx].2,
+            Err(_) => IdnaMapping::Disallowed
+        }
+    }
+
+""")
+    rows = load_idna_mapping("IdnaMappingTable.txt")
+    def fmt_row(row):
+        (lo, hi, status, mapping) = row
+        if status == "Mapped":
+            value = "Mapped(\"%s\")" % "".join("\\u{%x}" % c for c in mapping)
+        else:
+            value = status
+        return "(%s, %s, %s)" % (escape_char(lo), escape_char(hi), value)
+    f.write("    use self::IdnaMapping::*;\n\n")
+    f.write("    // IDNA mapping table:\n")
+    emit_table(f, "IDNA_MAPPING", rows, "&'static [(char, char, IdnaMapping)]", is_pub=False,
+            pfun=fmt_row)
+    f.write("}\n\n")
+
 def emit_util_mod(f):
     f.write("""
 pub mod util {
@@ -612,18 +1216,78 @@ pub mod util {
         }).is_ok()
     }
 
-    pub fn bsearch_range_value_table<T: Copy>(c: char, r: &'static [(char, char, T)]) -> Option<T> {
+    #[cfg(any(test, feature = "bench"))]
+    pub fn bsearch_range_value_table_all<T: Copy>(c: char, r: &'static [(char, char, T)]) -> &'static [(char, char, T)] {
         use core::cmp::Ordering::{Equal, Less, Greater};
+        // Finds all the entries for the range containing `c`, for tables
+        // which list a range once per value.
         match r.binary_search_by(|&(lo, hi, _)| {
             if lo <= c && c <= hi { Equal }
             else if hi < c { Less }
             else { Greater }
         }) {
             Ok(idx) => {
-                let (_, _, cat) = r[idx];
-                Some(cat)
+                let (lo, _, _) = r[idx];
+                let start = r[..idx].iter().rposition(|&(l, _, _)| l != lo).map_or(0, |i| i + 1);
+                let end = r[idx..].iter().position(|&(l, _, _)| l != lo).map_or(r.len(), |i| idx + i);
+                &r[start..end]
+            }
+            Err(_) => &[]
+        }
+    }
+
+    /// A set of code points, looked up by chunks of 4096 code points, then blocks
+    /// of 64 code points stored as bitmaps; identical chunks and blocks are shared.
+    ///
+    /// Each level also stores the number of code points of the set before it,
+    /// so that the position of a code point in the set can be found as well.
+    pub struct CharTrie {
+        /// The ASCII part of the set, for a faster lookup
+        pub ascii: u128,
+        /// The block table of each chunk, and the number of code points before the chunk
+        pub chunks: &'static [(u16, u32)],
+        /// The bitmap of each block of a chunk, and the number of code points
+        /// before the block in the chunk
+        pub blocks: &'static [[(u16, u16); 64]],
+        pub leaves: &'static [u64],
+    }
+
+    impl CharTrie {
+        #[inline]
+        fn leaf(&self, c: char) -> (u64, usize) {
+            let c = c as usize;
+            let (table, chunk_rank) = self.chunks[c >> 12];
+            let (leaf, block_rank) = self.blocks[table as usize][(c >> 6) & 0x3f];
+            (self.leaves[leaf as usize], chunk_rank as usize + block_rank as usize)
+        }
+
+        #[inline]
+        pub fn contains(&self, c: char) -> bool {
+            if (c as u32) < 0x80 {
+                return self.ascii >> (c as u32) & 1 != 0;
+            }
+            let (leaf, _) = self.leaf(c);
+            leaf >> (c as u32 & 0x3f) & 1 != 0
+        }
+
+        /// The number of code points of the set up to and including `c`
+        #[inline]
+        pub fn rank(&self, c: char) -> usize {
+            if (c as u32) < 0x80 {
+                return (self.ascii & (u128::MAX >> (0x7f - c as u32))).count_ones() as usize;
+            }
+            let (leaf, rank) = self.leaf(c);
+            rank + (leaf & (u64::MAX >> (0x3f - (c as u32 & 0x3f)))).count_ones() as usize
+        }
+
+        /// The position of `c` in the set, if it belongs to it
+        #[inline]
+        pub fn index(&self, c: char) -> Option<usize> {
+            if self.contains(c) {
+                Some(self.rank(c) - 1)
+            } else {
+                None
             }
-            Err(_) => None
         }
     }
 
@@ -651,5 +1315,17 @@ pub const UNICODE_VERSION: (u64, u64, u64) = (%s, %s, %s);
         emit_identifier_module(rf)
         ### confusable_detection module
         emit_confusable_detection_module(rf)
+        ### script_list module
+        emit_script_list_module(rf)
         ### mixed_script_confusable_detection module
         emit_potiential_mixed_script_confusable(rf)
+        ### whole_script_confusable module
+        emit_whole_script_confusable(rf)
+        ### decimal_digit module
+        emit_decimal_digit_module(rf)
+        ### nonspacing_mark module
+        emit_nonspacing_mark_module(rf)
+        ### xid module
+        emit_xid_module(rf)
+        ### idna_mapping module
+        emit_idna_mapping_module(rf)
@@ -0,0 +1,84 @@
+//! Benchmarks of the table lookups, comparing the tries with binary searches
+//! over the same tables. Run with `cargo +nightly bench --features bench`.
+
+use crate::tables::{confusable_detection, identifier};
+use test::{black_box, Bencher};
+
+/// Identifiers in a few scripts, with mostly ASCII as in typical source code
+const TEXT: &str = "fn parse_header(input: &[u8]) -> Result<Header, Error> { \
+                    let größe = input.len(); let σύνολο = 0; let значение = 1; \
+                    let 変数 = 2; let متغير = 3; let ｗｉｄｅ = 4; ﬁle µs Ⅳ }";
+
+#[bench]
+fn identifier_status_trie(b: &mut Bencher) {
+    b.iter(|| {
+        black_box(TEXT)
+            .chars()
+            .filter(|&c| identifier::identifier_status_allowed(c))
+            .count()
+    });
+}
+
+#[bench]
+fn identifier_status_bsearch(b: &mut Bencher) {
+    b.iter(|| {
+        black_box(TEXT)
+            .chars()
+            .filter(|&c| identifier::identifier_status_allowed_bsearch(c))
+            .count()
+    });
+}
+
+#[bench]
+fn identifier_types_trie(b: &mut Bencher) {
+    b.iter(|| {
+        black_box(TEXT)
+            .chars()
+            .map(|c| identifier::identifier_types(c).len())
+            .sum::<usize>()
+    });
+}
+
+#[bench]
+fn identifier_types_bsearch(b: &mut Bencher) {
+    b.iter(|| {
+        black_box(TEXT)
+            .chars()
+            .map(|c| identifier::identifier_types_bsearch(c).len())
+            .sum::<usize>()
+    });
+}
+
+#[bench]
+fn confusable_prototype_trie(b: &mut Bencher) {
+    b.iter(|| {
+        black_box(TEXT)
+            .chars()
+            .filter_map(confusable_detection::char_confusable_prototype)
+            .count()
+    });
+}
+
+#[bench]
+fn confusable_prototype_bsearch(b: &mut Bencher) {
+    b.iter(|| {
+        black_box(TEXT)
+            .chars()
+            .filter_map(confusable_detection::char_confusable_prototype_bsearch)
+            .count()
+    });
+}
+
+/// Punctuation and marks shared by several scripts, with multi-script Script_Extensions
+const SHARED_TEXT: &str = "।॥،؛؟ー・〜゙゚᳐᳒ـ॰ꣳ।॥،؛؟ー・〜゙゚᳐᳒ـ॰ꣳ";
+
+#[bench]
+fn augmented_script_set_shared(b: &mut Bencher) {
+    use crate::mixed_script::AugmentedScriptSet;
+    b.iter(|| {
+        black_box(SHARED_TEXT)
+            .chars()
+            .map(|c| AugmentedScriptSet::for_char(c).len())
+            .sum::<usize>()
+    });
+}
@@ -0,0 +1,49 @@
+//! Reordering of a single paragraph to visual order for [`bidi_skeleton`](crate::confusable_detection::bidi_skeleton)
+//!
+//! The [Unicode Bidirectional Algorithm](https://www.unicode.org/reports/tr9/) and its
+//! Bidi_Class and Bidi_Paired_Bracket data are those of the `unicode-bidi` crate, and the
+//! Bidi_Mirroring_Glyph data is that of the `unicode-bidi-mirroring` crate. Their Unicode
+//! version is [`unicode_bidi::UNICODE_VERSION`], which may be newer than
+//! [`UNICODE_VERSION`](crate::UNICODE_VERSION).
+//!
+//! Line breaking is not supported, the whole paragraph is one line.
+
+use crate::confusable_detection::Direction;
+use alloc::string::String;
+use unicode_bidi::{bidi_class, BidiClass, Level, ParagraphBidiInfo};
+use unicode_bidi_mirroring::get_mirrored;
+
+/// Explicit formatting characters, i.e. those removed by rule X9 and the isolate formatting characters
+fn is_explicit_formatting(c: char) -> bool {
+    use BidiClass::*;
+    matches!(
+        bidi_class(c),
+        RLE | LRE | RLO | LRO | PDF | BN | LRI | RLI | FSI | PDI
+    )
+}
+
+/// Reorder a paragraph to visual order, mirroring the characters at odd levels
+///
+/// Explicit formatting characters are left out since they are invisible.
+pub(crate) fn visual_order(text: &str, direction: Direction) -> String {
+    let para_level = match direction {
+        Direction::LeftToRight => Level::ltr(),
+        Direction::RightToLeft => Level::rtl(),
+    };
+    let info = ParagraphBidiInfo::new(text, Some(para_level));
+    let (levels, runs) = info.visual_runs(0..text.len());
+
+    let mut visual = String::with_capacity(text.len());
+    for run in runs {
+        let chars = text[run.clone()]
+            .chars()
+            .filter(|&c| !is_explicit_formatting(c));
+        if levels[run.start].is_rtl() {
+            // L4
+            visual.extend(chars.rev().map(|c| get_mirrored(c).unwrap_or(c)));
+        } else {
+            visual.extend(chars);
+        }
+    }
+    visual
+}
@@ -1,6 +1,13 @@
 //! [Confusable detection](https://www.unicode.org/reports/tr39/#Confusable_Detection)
 
+use crate::mixed_script::{AugmentedScriptSet, MixedScript};
+#[cfg(feature = "bidi")]
+use alloc::string::String;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
 use core::iter;
+use core::str::Chars;
+use unicode_script::Script;
 
 enum OnceOrMore<T, I> {
     Once(iter::Once<T>),
@@ -22,13 +29,11 @@ where
     }
 }
 
-type StaticSliceIterCloned = core::iter::Cloned<core::slice::Iter<'static, char>>;
-
-fn char_prototype(c: char) -> OnceOrMore<char, StaticSliceIterCloned> {
+fn char_prototype(c: char) -> OnceOrMore<char, Chars<'static>> {
     use crate::tables::confusable_detection::char_confusable_prototype;
     match char_confusable_prototype(c) {
         None => OnceOrMore::Once(iter::once(c)),
-        Some(l) => OnceOrMore::More(l.iter().cloned()),
+        Some(s) => OnceOrMore::More(s.chars()),
     }
 }
 
@@ -37,3 +42,312 @@ pub fn skeleton(s: &str) -> impl Iterator<Item = char> + '_ {
     use unicode_normalization::UnicodeNormalization;
     s.chars().nfd().flat_map(char_prototype).nfd()
 }
+
+/// Get the prototype a character is mapped to in `confusables.txt`
+///
+/// Returns `None` for characters which are their own prototype. Note that [skeleton]
+/// also normalizes to NFD before and after mapping the characters to their prototypes.
+pub fn confusable_prototype(c: char) -> Option<&'static str> {
+    crate::tables::confusable_detection::char_confusable_prototype(c)
+}
+
+/// Find the characters and sequences which have the same prototype as a character
+/// in `confusables.txt`, i.e. the inverse of [`confusable_prototype`]
+///
+/// This yields the prototype itself first, unless it is the character, then the other characters
+/// mapped to it in code point order. Characters which are neither mapped to a prototype nor
+/// the prototype of another character yield nothing.
+pub fn confusables_of(c: char) -> Confusables<'static> {
+    use crate::tables::confusable_detection::{char_confusable_prototype_id, find_prototype};
+    let id = char_confusable_prototype_id(c).or_else(|| {
+        let mut buf = [0; 4];
+        let c: &str = c.encode_utf8(&mut buf);
+        find_prototype(|prototype| prototype.cmp(c))
+    });
+    Confusables::new(id, Query::Char(c))
+}
+
+/// Find the characters and sequences whose [skeleton] is the skeleton of a string,
+/// among the prototypes and the characters mapped to them in `confusables.txt`
+///
+/// This yields the prototype first, unless it is the string itself, then the characters
+/// mapped to it in code point order, except the string itself. For instance, `"rn"` yields `"m"`
+/// among others, and `"m"` yields `"rn"`. Strings with a skeleton which is not a prototype,
+/// like most words, yield nothing.
+pub fn confusables_of_str(s: &str) -> Confusables<'_> {
+    use crate::tables::confusable_detection::find_prototype;
+    let id = find_prototype(|prototype| prototype.chars().cmp(skeleton(s)));
+    Confusables::new(id, Query::Str(s))
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Query<'a> {
+    Char(char),
+    Str(&'a str),
+}
+
+/// An iterator over the characters and sequences with the same prototype as a character or string.
+///
+/// This is created by [`confusables_of`] and [`confusables_of_str`].
+#[derive(Clone, Debug)]
+pub struct Confusables<'a> {
+    query: Query<'a>,
+    prototype: Option<&'static str>,
+    sources: &'static str,
+}
+
+impl<'a> Confusables<'a> {
+    fn new(id: Option<u16>, query: Query<'a>) -> Self {
+        use crate::tables::confusable_detection::{prototype_sources, prototype_str};
+        Confusables {
+            query,
+            prototype: id.map(prototype_str),
+            sources: id.map_or("", prototype_sources),
+        }
+    }
+
+    fn is_query(&self, candidate: &str) -> bool {
+        match self.query {
+            Query::Char(c) => candidate.chars().eq(iter::once(c)),
+            Query::Str(s) => candidate == s,
+        }
+    }
+}
+
+impl Iterator for Confusables<'_> {
+    type Item = &'static str;
+
+    fn next(&mut self) -> Option<&'static str> {
+        if let Some(prototype) = self.prototype.take() {
+            if !self.is_query(prototype) {
+                return Some(prototype);
+            }
+        }
+        while let Some(c) = self.sources.chars().next() {
+            let (source, rest) = self.sources.split_at(c.len_utf8());
+            self.sources = rest;
+            if !self.is_query(source) {
+                return Some(source);
+            }
+        }
+        None
+    }
+}
+
+/// A paragraph direction, for [`bidi_skeleton`]
+///
+/// This requires the `bidi` feature.
+#[cfg(feature = "bidi")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum Direction {
+    /// Left-to-right
+    LeftToRight,
+    /// Right-to-left
+    RightToLeft,
+}
+
+/// Calculate the [bidi skeleton](https://www.unicode.org/reports/tr39/#def-bidi-skeleton) of a string,
+/// displayed in a paragraph with the given direction
+///
+/// The string is converted to NFD and reordered to visual order by the
+/// [Unicode Bidirectional Algorithm](https://www.unicode.org/reports/tr9/), mirroring the characters
+/// displayed right-to-left and leaving out the explicit formatting characters, before taking its [skeleton].
+/// Strings with the same bidi skeleton look alike when displayed with that direction,
+/// even if their characters are stored in a different order.
+///
+/// This requires the `bidi` feature.
+#[cfg(feature = "bidi")]
+pub fn bidi_skeleton(s: &str, direction: Direction) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    let nfd: String = s.chars().nfd().collect();
+    skeleton(&crate::bidi::visual_order(&nfd, direction)).collect()
+}
+
+/// Check if two strings are [bidi confusable](https://www.unicode.org/reports/tr39/#def-bidi-confusable)
+/// when displayed in a paragraph with the given direction, i.e. have the same [`bidi_skeleton`]
+///
+/// This requires the `bidi` feature.
+#[cfg(feature = "bidi")]
+pub fn is_bidi_confusable(a: &str, b: &str, direction: Direction) -> bool {
+    a == b || bidi_skeleton(a, direction) == bidi_skeleton(b, direction)
+}
+
+/// Check if two strings have the same [skeleton]
+///
+/// The skeletons are computed lazily and compared character by character,
+/// stopping at the first difference; neither skeleton is collected into a buffer.
+pub fn skeleton_eq(a: &str, b: &str) -> bool {
+    a == b || skeleton(a).eq(skeleton(b))
+}
+
+/// Compare the [skeleton]s of two strings lexicographically
+///
+/// Like [`skeleton_eq`], this stops at the first difference between the skeletons.
+pub fn skeleton_cmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    skeleton(a).cmp(skeleton(b))
+}
+
+/// Feed the [skeleton] of a string into a [`Hasher`]
+///
+/// Strings with the same skeleton produce the same hash.
+pub fn skeleton_hash<H: Hasher>(s: &str, state: &mut H) {
+    for c in skeleton(s) {
+        c.hash(state);
+    }
+    // Same terminator as `str` uses, so that keys made of several skeletons
+    // don't collide trivially.
+    state.write_u8(0xff);
+}
+
+/// A string compared and hashed by its [skeleton]
+///
+/// Two keys are equal exactly when their strings are [confusable](https://www.unicode.org/reports/tr39/#def-confusable),
+/// so a `HashSet<SkeletonKey>` detects confusable collisions among its members
+/// without ever materializing a skeleton.
+#[derive(Copy, Clone, Debug)]
+pub struct SkeletonKey<'a>(pub &'a str);
+
+impl<'a> SkeletonKey<'a> {
+    /// Get the wrapped string
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<'a> From<&'a str> for SkeletonKey<'a> {
+    fn from(s: &'a str) -> Self {
+        SkeletonKey(s)
+    }
+}
+
+impl PartialEq for SkeletonKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        skeleton_eq(self.0, other.0)
+    }
+}
+
+impl Eq for SkeletonKey<'_> {}
+
+impl PartialOrd for SkeletonKey<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SkeletonKey<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        skeleton_cmp(self.0, other.0)
+    }
+}
+
+impl Hash for SkeletonKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        skeleton_hash(self.0, state)
+    }
+}
+
+/// Iterate over the characters of a string that carry script information,
+/// i.e. the NFD form of the string without Common and Inherited characters
+fn script_significant_chars(s: &str) -> impl Iterator<Item = char> + '_ {
+    use unicode_normalization::UnicodeNormalization;
+    s.chars()
+        .nfd()
+        .filter(|&c| !AugmentedScriptSet::for_char(c).is_all())
+}
+
+/// Check if a string is [whole-script confusable](https://www.unicode.org/reports/tr39/#def-whole-script-confusables)
+/// with some string in the given script
+///
+/// The string itself must be [single-script](https://www.unicode.org/reports/tr39/#def-single-script),
+/// and must contain at least one character that is neither Common nor Inherited.
+pub fn is_whole_script_confusable_with(s: &str, script: Script) -> bool {
+    use crate::tables::whole_script_confusable::whole_script_confusable_targets;
+
+    if !s.is_single_script() {
+        return false;
+    }
+    let mut significant_chars = script_significant_chars(s).peekable();
+    significant_chars.peek().is_some()
+        && significant_chars.all(|c| whole_script_confusable_targets(c).contains(&script))
+}
+
+/// Find the scripts a string is [whole-script confusable](https://www.unicode.org/reports/tr39/#def-whole-script-confusables)
+/// with
+///
+/// See [`is_whole_script_confusable_with`] for the requirements on the string.
+pub fn whole_script_confusables(s: &str) -> WholeScriptConfusables<'_> {
+    use crate::tables::whole_script_confusable::whole_script_confusable_targets;
+
+    let candidates = match script_significant_chars(s).next() {
+        Some(c) => whole_script_confusable_targets(c),
+        None => &[],
+    };
+    WholeScriptConfusables {
+        s,
+        candidates: candidates.iter(),
+    }
+}
+
+/// An iterator over the scripts a string is whole-script confusable with.
+///
+/// This is created by [`whole_script_confusables`].
+#[derive(Clone, Debug)]
+pub struct WholeScriptConfusables<'a> {
+    s: &'a str,
+    candidates: core::slice::Iter<'static, Script>,
+}
+
+impl Iterator for WholeScriptConfusables<'_> {
+    type Item = Script;
+
+    fn next(&mut self) -> Option<Script> {
+        let s = self.s;
+        self.candidates
+            .by_ref()
+            .cloned()
+            .find(|&script| is_whole_script_confusable_with(s, script))
+    }
+}
+
+/// Check if two strings are [single-script confusable](https://www.unicode.org/reports/tr39/#def-single-script-confusables)
+///
+/// Two strings are single-script confusable if they have the same [skeleton]
+/// and are both [single-script](https://www.unicode.org/reports/tr39/#def-single-script)
+/// with a common script in their resolved script sets.
+pub fn is_single_script_confusable(a: &str, b: &str) -> bool {
+    let mut set = AugmentedScriptSet::for_str(a);
+    set.intersect_with(AugmentedScriptSet::for_str(b));
+    !set.is_empty() && skeleton_eq(a, b)
+}
+
+/// Extension trait for [confusable detection](https://www.unicode.org/reports/tr39/#Confusable_Detection)
+/// between two strings
+#[allow(clippy::wrong_self_convention)]
+pub trait Confusable {
+    /// Check if the two strings are [confusable](https://www.unicode.org/reports/tr39/#def-confusable),
+    /// i.e. have the same [skeleton]
+    fn is_confusable_with(self, other: &str) -> bool;
+
+    /// Check if the two strings are [single-script confusable](https://www.unicode.org/reports/tr39/#def-single-script-confusables)
+    fn is_single_script_confusable_with(self, other: &str) -> bool;
+
+    /// Check if the two strings are [mixed-script confusable](https://www.unicode.org/reports/tr39/#def-mixed-script-confusables)
+    fn is_mixed_script_confusable_with(self, other: &str) -> bool;
+}
+
+impl Confusable for &'_ str {
+    fn is_confusable_with(self, other: &str) -> bool {
+        skeleton_eq(self, other)
+    }
+
+    fn is_single_script_confusable_with(self, other: &str) -> bool {
+        is_single_script_confusable(self, other)
+    }
+
+    fn is_mixed_script_confusable_with(self, other: &str) -> bool {
+        crate::mixed_script::mixed_script_confusable(self, other).is_some()
+    }
+}
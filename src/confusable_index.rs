@@ -0,0 +1,170 @@
+//! An index of identifiers keyed by their [skeleton](crate::skeleton), for detecting
+//! [confusable](https://www.unicode.org/reports/tr39/#Confusable_Detection) registrations
+//!
+//! This module requires the `alloc` feature.
+
+use crate::mixed_script::AugmentedScriptSet;
+use crate::skeleton;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::slice;
+
+/// The kind of confusable relation between two distinct strings with the same skeleton
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum ConfusableKind {
+    /// https://www.unicode.org/reports/tr39/#def-single-script-confusables
+    SingleScript,
+    /// https://www.unicode.org/reports/tr39/#def-mixed-script-confusables
+    ///
+    /// This does not include whole-script confusables.
+    MixedScript,
+    /// https://www.unicode.org/reports/tr39/#def-whole-script-confusables
+    WholeScript,
+}
+
+impl ConfusableKind {
+    /// Classify two strings with the same skeleton by their resolved script sets
+    pub fn classify(a: AugmentedScriptSet, b: AugmentedScriptSet) -> Self {
+        let mut common = a;
+        common.intersect_with(b);
+        if !common.is_empty() {
+            ConfusableKind::SingleScript
+        } else if !a.is_empty() && !b.is_empty() {
+            ConfusableKind::WholeScript
+        } else {
+            ConfusableKind::MixedScript
+        }
+    }
+}
+
+/// A previously registered name that a queried name is confusable with
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ConfusableMatch<'a> {
+    /// The registered name
+    pub name: &'a str,
+    /// The resolved script set of the registered name
+    pub scripts: AugmentedScriptSet,
+    /// How the queried name is confusable with the registered name
+    pub kind: ConfusableKind,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    name: String,
+    scripts: AugmentedScriptSet,
+}
+
+/// An incremental registry of names keyed by their [skeleton](crate::skeleton)
+///
+/// Looking up the names a new name is confusable with takes logarithmic time
+/// in the number of registered names, however the names were chosen.
+#[derive(Clone, Debug, Default)]
+pub struct ConfusableIndex {
+    entries: Vec<Entry>,
+    /// Indices into `entries`, by skeleton
+    skeletons: BTreeMap<String, Vec<usize>>,
+}
+
+impl ConfusableIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of registered names
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no names are registered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Check if exactly this name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        let skeleton = skeleton(name).collect::<String>();
+        self.with_skeleton(&skeleton)
+            .iter()
+            .any(|&idx| self.entries[idx].name == name)
+    }
+
+    /// Register a name
+    ///
+    /// Returns `false` if exactly this name was already registered.
+    pub fn insert(&mut self, name: &str) -> bool {
+        let skeleton = skeleton(name).collect::<String>();
+        let idx = self.entries.len();
+        let entries = &self.entries;
+        let indices = self.skeletons.entry(skeleton).or_default();
+        if indices.iter().any(|&idx| entries[idx].name == name) {
+            return false;
+        }
+        indices.push(idx);
+        self.entries.push(Entry {
+            name: name.into(),
+            scripts: AugmentedScriptSet::for_str(name),
+        });
+        true
+    }
+
+    /// Find the registered names the given name is confusable with
+    ///
+    /// The name itself is never reported, even if it is registered.
+    pub fn confusables_of(&self, name: &str) -> ConfusableMatches<'_> {
+        let skeleton = skeleton(name).collect::<String>();
+        let candidates = self.with_skeleton(&skeleton);
+        let exact = candidates
+            .iter()
+            .cloned()
+            .find(|&idx| self.entries[idx].name == name);
+        ConfusableMatches {
+            candidates: candidates.iter(),
+            entries: &self.entries,
+            exact,
+            scripts: AugmentedScriptSet::for_str(name),
+        }
+    }
+
+    /// Iterate over the registered names, in insertion order
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.entries.iter().map(|entry| &*entry.name)
+    }
+
+    /// The indices of the entries with the given skeleton
+    fn with_skeleton(&self, skeleton: &str) -> &[usize] {
+        self.skeletons.get(skeleton).map_or(&[], |indices| indices)
+    }
+}
+
+/// An iterator over the registered names a name is confusable with.
+///
+/// This is created by [`ConfusableIndex::confusables_of`].
+#[derive(Clone, Debug)]
+pub struct ConfusableMatches<'a> {
+    candidates: slice::Iter<'a, usize>,
+    entries: &'a [Entry],
+    /// The entry of the queried name itself, if registered
+    exact: Option<usize>,
+    scripts: AugmentedScriptSet,
+}
+
+impl<'a> Iterator for ConfusableMatches<'a> {
+    type Item = ConfusableMatch<'a>;
+
+    fn next(&mut self) -> Option<ConfusableMatch<'a>> {
+        for &idx in self.candidates.by_ref() {
+            let entry = &self.entries[idx];
+            if Some(idx) == self.exact {
+                continue;
+            }
+            return Some(ConfusableMatch {
+                name: &entry.name,
+                scripts: entry.scripts,
+                kind: ConfusableKind::classify(self.scripts, entry.scripts),
+            });
+        }
+        None
+    }
+}
@@ -0,0 +1,296 @@
+//! Spoofing checks for [domain names](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection),
+//! applied label by label after [UTS #46](https://www.unicode.org/reports/tr46/) mapping
+//!
+//! This module requires the `domain` feature.
+
+use crate::confusable_index::ConfusableIndex;
+use crate::mixed_script::AugmentedScriptSet;
+use crate::restriction_level::{RestrictionLevel, RestrictionLevelDetection};
+use crate::tables::idna_mapping::{idna_mapping, IdnaMapping};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::Split;
+use unicode_normalization::UnicodeNormalization;
+
+/// The prefix of labels encoded with Punycode
+const ACE_PREFIX: &str = "xn--";
+
+/// A reason for a domain name to be invalid
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum DomainError {
+    /// A character is disallowed by UTS #46 for hostnames
+    DisallowedChar {
+        /// Byte offset of the character in the original domain name
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// A label is empty
+    EmptyLabel {
+        /// The position of the label in the domain name
+        label: usize,
+    },
+    /// A label starting with `xn--` is not valid Punycode, or decodes to a label
+    /// which is ASCII only or which the UTS #46 mapping would change
+    Punycode {
+        /// The position of the label in the domain name
+        label: usize,
+    },
+}
+
+impl fmt::Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            DomainError::DisallowedChar { index, ch } => {
+                write!(f, "disallowed character {:?} at byte {}", ch, index)
+            }
+            DomainError::EmptyLabel { label } => write!(f, "label {} is empty", label),
+            DomainError::Punycode { label } => write!(f, "label {} is not valid Punycode", label),
+        }
+    }
+}
+
+/// Map a domain name as UTS #46 does for hostnames, and decode its Punycode labels
+///
+/// This uses nontransitional processing with UseSTD3ASCIIRules. A trailing dot is removed.
+/// The other validity criteria of UTS #46, such as the hyphen and bidi rules, are not checked.
+pub fn map_domain(domain: &str) -> Result<String, DomainError> {
+    let mut mapped = String::with_capacity(domain.len());
+    for (index, ch) in domain.char_indices() {
+        match idna_mapping(ch) {
+            IdnaMapping::Valid => mapped.push(ch),
+            IdnaMapping::Ignored => {}
+            IdnaMapping::Mapped(s) => mapped.push_str(s),
+            IdnaMapping::Disallowed => return Err(DomainError::DisallowedChar { index, ch }),
+        }
+    }
+    let normalized: String = mapped.nfc().collect();
+    let normalized = normalized.strip_suffix('.').unwrap_or(&normalized);
+
+    let mut result = String::with_capacity(normalized.len());
+    for (label, text) in normalized.split('.').enumerate() {
+        if label > 0 {
+            result.push('.');
+        }
+        if text.is_empty() {
+            return Err(DomainError::EmptyLabel { label });
+        }
+        match text.strip_prefix(ACE_PREFIX) {
+            Some(encoded) => match decode_punycode(encoded) {
+                Some(decoded) if is_mapped_label(&decoded) => result.extend(decoded),
+                _ => return Err(DomainError::Punycode { label }),
+            },
+            None => result.push_str(text),
+        }
+    }
+    Ok(result)
+}
+
+/// Check if a decoded Punycode label is one the UTS #46 mapping could have produced,
+/// so that Punycode can not smuggle in characters the mapping removes or disallows
+fn is_mapped_label(decoded: &[char]) -> bool {
+    !decoded.iter().all(char::is_ascii)
+        && decoded
+            .iter()
+            .all(|&ch| ch != '.' && matches!(idna_mapping(ch), IdnaMapping::Valid))
+        && decoded.iter().cloned().nfc().eq(decoded.iter().cloned())
+}
+
+/// Decode a Punycode label as defined by [RFC 3492](https://tools.ietf.org/html/rfc3492),
+/// returning `None` if it is malformed
+fn decode_punycode(input: &str) -> Option<Vec<char>> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    const SKEW: u32 = 38;
+    const DAMP: u32 = 700;
+
+    fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+        delta /= if first_time { DAMP } else { 2 };
+        delta += delta / num_points;
+        let mut k = 0;
+        while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+        k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+    }
+
+    let (basic, encoded) = match input.rfind('-') {
+        Some(idx) => (&input[..idx], &input[idx + 1..]),
+        None => ("", input),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+    let mut n: u32 = 0x80;
+    let mut bias: u32 = 72;
+    let mut i: u32 = 0;
+    let mut bytes = encoded.bytes().peekable();
+    while bytes.peek().is_some() {
+        let old_i = i;
+        let mut weight: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = match bytes.next()? {
+                byte @ b'a'..=b'z' => byte - b'a',
+                byte @ b'A'..=b'Z' => byte - b'A',
+                byte @ b'0'..=b'9' => byte - b'0' + 26,
+                _ => return None,
+            } as u32;
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+            let t = if k <= bias {
+                T_MIN
+            } else if k >= bias + T_MAX {
+                T_MAX
+            } else {
+                k - bias
+            };
+            if digit < t {
+                break;
+            }
+            weight = weight.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, core::char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output)
+}
+
+/// A spoofing issue found in a domain name by [`DomainChecker::check`]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum DomainIssue<'a> {
+    /// The label is not [single-script](https://www.unicode.org/reports/tr39/#def-single-script);
+    /// an ASCII top-level domain is not checked
+    MixedScript {
+        /// The position of the suspicious label
+        label: usize,
+    },
+    /// A label is less strict than the restriction level of the checker
+    RestrictionLevel {
+        /// The position of the suspicious label
+        label: usize,
+        /// The restriction level of the label
+        level: RestrictionLevel,
+    },
+    /// The domain name is [confusable](https://www.unicode.org/reports/tr39/#def-confusable)
+    /// with a protected domain name
+    Lookalike {
+        /// The position of the first label which differs from the protected domain name, or `None`
+        /// if the labels of the shorter of the two names are the same in the other
+        label: Option<usize>,
+        /// The protected domain name, as mapped by [`map_domain`]
+        protected: &'a str,
+    },
+}
+
+/// Checks domain names for spoofing, label by label, and against a list of protected domain names
+#[derive(Clone, Debug)]
+pub struct DomainChecker {
+    protected: ConfusableIndex,
+    restriction_level: RestrictionLevel,
+}
+
+impl Default for DomainChecker {
+    fn default() -> Self {
+        DomainChecker {
+            protected: ConfusableIndex::new(),
+            restriction_level: RestrictionLevel::HighlyRestrictive,
+        }
+    }
+}
+
+impl DomainChecker {
+    /// Create a checker without protected domain names, requiring labels
+    /// to be [highly restrictive](RestrictionLevel::HighlyRestrictive) or stricter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the least strict restriction level allowed for labels
+    pub fn restriction_level(mut self, level: RestrictionLevel) -> Self {
+        self.restriction_level = level;
+        self
+    }
+
+    /// Add a protected domain name, which other domain names should not look like
+    pub fn protect(&mut self, domain: &str) -> Result<(), DomainError> {
+        self.protected.insert(&map_domain(domain)?);
+        Ok(())
+    }
+
+    /// Map a domain name with [`map_domain`] and check it for spoofing
+    pub fn check(&self, domain: &str) -> Result<DomainReport<'_>, DomainError> {
+        let domain = map_domain(domain)?;
+        let mut issues = Vec::new();
+
+        let labels: Vec<&str> = domain.split('.').collect();
+        let mut checked_labels = labels.len();
+        if labels.len() > 1 && labels[labels.len() - 1].is_ascii() {
+            checked_labels -= 1;
+        }
+        for (label, text) in labels[..checked_labels].iter().enumerate() {
+            if AugmentedScriptSet::for_str(text).is_empty() {
+                issues.push(DomainIssue::MixedScript { label });
+            }
+        }
+        for (label, text) in labels.iter().enumerate() {
+            let level = text.detect_restriction_level();
+            if level > self.restriction_level {
+                issues.push(DomainIssue::RestrictionLevel { label, level });
+            }
+        }
+        for confusable in self.protected.confusables_of(&domain) {
+            let label = labels
+                .iter()
+                .zip(confusable.name.split('.'))
+                .position(|(&a, b)| a != b);
+            issues.push(DomainIssue::Lookalike {
+                label,
+                protected: confusable.name,
+            });
+        }
+        Ok(DomainReport { domain, issues })
+    }
+}
+
+/// The result of [`DomainChecker::check`]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct DomainReport<'a> {
+    domain: String,
+    issues: Vec<DomainIssue<'a>>,
+}
+
+impl<'a> DomainReport<'a> {
+    /// The domain name, as mapped by [`map_domain`]
+    pub fn as_str(&self) -> &str {
+        &self.domain
+    }
+
+    /// Iterate over the labels of the mapped domain name
+    pub fn labels(&self) -> Split<'_, char> {
+        self.domain.split('.')
+    }
+
+    /// Get the label at the given position in the mapped domain name
+    pub fn label(&self, label: usize) -> Option<&str> {
+        self.labels().nth(label)
+    }
+
+    /// The spoofing issues found
+    pub fn issues(&self) -> &[DomainIssue<'a>] {
+        &self.issues
+    }
+
+    /// Check if any spoofing issue was found
+    pub fn is_suspicious(&self) -> bool {
+        !self.issues.is_empty()
+    }
+}
@@ -0,0 +1,167 @@
+//! The [Email Security Profile](https://www.unicode.org/reports/tr39/#Email_Security_Profiles)
+//! for the local part of email addresses
+
+use crate::confusable_detection::skeleton;
+use crate::mixed_number::MixedNumbers;
+use crate::optional_detection::{
+    nonspacing_mark_issues, NonspacingMarkIssue, DEFAULT_MAX_NONSPACING_MARKS,
+};
+use crate::restriction_level::{restriction_level_report, RestrictionLevel};
+use crate::GeneralSecurityProfile;
+use core::fmt;
+
+/// The separator between the atoms of a local part, as in `john.doe`
+pub const ATOM_SEPARATOR: char = '.';
+
+/// The separator between the mailbox and the subaddress (or tag) of a local part, as in `john+news`
+pub const SUBADDRESS_SEPARATOR: char = '+';
+
+/// The least strict restriction level allowed for local parts
+pub const MAX_RESTRICTION_LEVEL: RestrictionLevel = RestrictionLevel::HighlyRestrictive;
+
+/// A reason for a local part to fail the Email Security Profile
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum EmailError {
+    /// The local part is empty
+    Empty,
+    /// A dot starts or ends the local part, or follows another dot
+    MisplacedDot {
+        /// Byte offset of the dot
+        index: usize,
+    },
+    /// A character is not allowed in local parts
+    ///
+    /// ASCII characters must be `atext` as defined by RFC 5322, other characters must be
+    /// allowed in identifiers by the General Security Profile.
+    InvalidChar {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// The local part is not in NFKC
+    NotNfkc,
+    /// The local part is less strict than [`MAX_RESTRICTION_LEVEL`]
+    RestrictionLevel {
+        /// The restriction level of the local part
+        level: RestrictionLevel,
+        /// Byte offset of the character which first exceeded the allowed level
+        index: usize,
+        /// The character which first exceeded the allowed level
+        ch: char,
+    },
+    /// The local part uses digits from several decimal number systems
+    MixedNumbers,
+    /// The local part has a suspicious sequence of nonspacing marks
+    NonspacingMarks(NonspacingMarkIssue),
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            EmailError::Empty => f.write_str("empty local part"),
+            EmailError::MisplacedDot { index } => write!(f, "misplaced dot at byte {}", index),
+            EmailError::InvalidChar { index, ch } => {
+                write!(f, "invalid character {:?} at byte {}", ch, index)
+            }
+            EmailError::NotNfkc => f.write_str("local part is not in NFKC"),
+            EmailError::RestrictionLevel { level, index, ch } => write!(
+                f,
+                "local part is {} because of {:?} at byte {}",
+                level, ch, index
+            ),
+            EmailError::MixedNumbers => f.write_str("local part mixes number systems"),
+            EmailError::NonspacingMarks(_) => {
+                f.write_str("local part has suspicious nonspacing marks")
+            }
+        }
+    }
+}
+
+/// Check if an ASCII character is `atext` as defined by RFC 5322
+fn is_atext(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(ch)
+}
+
+/// Split a local part into its mailbox and its subaddress, at the first [`SUBADDRESS_SEPARATOR`]
+pub fn split_subaddress(local_part: &str) -> (&str, Option<&str>) {
+    match local_part.find(SUBADDRESS_SEPARATOR) {
+        Some(idx) => (&local_part[..idx], Some(&local_part[idx + 1..])),
+        None => (local_part, None),
+    }
+}
+
+/// Check that a local part satisfies the Email Security Profile, returning the first problem found
+///
+/// The local part must be a `dot-atom-text` as defined by RFC 5322, extended to the non-ASCII
+/// characters allowed by the General Security Profile. It must also be in NFKC, be
+/// [highly restrictive](RestrictionLevel::HighlyRestrictive) or stricter, not mix number systems
+/// and not have suspicious nonspacing marks; the ASCII symbols of `atext`, which the
+/// General Security Profile does not allow, are ignored for the
+/// [restriction level](crate::RestrictionLevelDetection).
+pub fn validate_local_part(local_part: &str) -> Result<(), EmailError> {
+    if local_part.is_empty() {
+        return Err(EmailError::Empty);
+    }
+    let mut prev_dot = true;
+    for (index, ch) in local_part.char_indices() {
+        if ch == ATOM_SEPARATOR {
+            if prev_dot {
+                return Err(EmailError::MisplacedDot { index });
+            }
+            prev_dot = true;
+            continue;
+        }
+        prev_dot = false;
+        let valid = if ch.is_ascii() {
+            is_atext(ch)
+        } else {
+            ch.identifier_allowed()
+        };
+        if !valid {
+            return Err(EmailError::InvalidChar { index, ch });
+        }
+    }
+    if prev_dot {
+        return Err(EmailError::MisplacedDot {
+            index: local_part.len() - 1,
+        });
+    }
+    if !unicode_normalization::is_nfkc(local_part) {
+        return Err(EmailError::NotNfkc);
+    }
+    // ASCII symbols are Common, so leaving them out only keeps them from
+    // making the local part unrestricted
+    let report = restriction_level_report(
+        local_part
+            .char_indices()
+            .filter(|&(_, ch)| !ch.is_ascii_punctuation()),
+    );
+    if let Some((index, ch)) = report.first_violation(MAX_RESTRICTION_LEVEL) {
+        return Err(EmailError::RestrictionLevel {
+            level: report.level,
+            index,
+            ch,
+        });
+    }
+    if local_part.has_mixed_numbers() {
+        return Err(EmailError::MixedNumbers);
+    }
+    if let Some(issue) = nonspacing_mark_issues(local_part, DEFAULT_MAX_NONSPACING_MARKS).next() {
+        return Err(EmailError::NonspacingMarks(issue));
+    }
+    Ok(())
+}
+
+/// Calculate the [skeleton] of the mailbox of a local part, ignoring its subaddress
+///
+/// Dots are kept, since only some mail providers ignore them.
+pub fn local_part_skeleton(local_part: &str) -> impl Iterator<Item = char> + '_ {
+    skeleton(split_subaddress(local_part).0)
+}
+
+/// Check if two local parts address [confusable](https://www.unicode.org/reports/tr39/#def-confusable)
+/// mailboxes, i.e. have the same [`local_part_skeleton`]
+pub fn is_local_part_confusable(a: &str, b: &str) -> bool {
+    local_part_skeleton(a).eq(local_part_skeleton(b))
+}
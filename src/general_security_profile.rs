@@ -2,16 +2,120 @@
 //! for identifiers
 
 use crate::tables::identifier;
+use core::iter::FromIterator;
+
+#[cfg(feature = "alloc")]
+use crate::mixed_script::ScriptBitSet;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use unicode_script::{Script, UnicodeScript};
 
 pub use identifier::IdentifierType;
 
+/// Every identifier type, in the order of the `IdentifierType` enum
+const IDENTIFIER_TYPES: [IdentifierType; 12] = [
+    IdentifierType::Not_Character,
+    IdentifierType::Deprecated,
+    IdentifierType::Default_Ignorable,
+    IdentifierType::Not_NFKC,
+    IdentifierType::Not_XID,
+    IdentifierType::Exclusion,
+    IdentifierType::Obsolete,
+    IdentifierType::Technical,
+    IdentifierType::Uncommon_Use,
+    IdentifierType::Limited_Use,
+    IdentifierType::Inclusion,
+    IdentifierType::Recommended,
+];
+
+/// A set of [identifier types](https://www.unicode.org/reports/tr39/#Identifier_Status_and_Type)
+///
+/// A character can have several identifier types at once, such as `Technical Uncommon_Use`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub struct IdentifierTypeSet {
+    bits: u16,
+}
+
+impl IdentifierTypeSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an identifier type to the set
+    pub fn insert(&mut self, ty: IdentifierType) {
+        self.bits |= 1 << ty as u16;
+    }
+
+    /// Remove an identifier type from the set
+    pub fn remove(&mut self, ty: IdentifierType) {
+        self.bits &= !(1 << ty as u16);
+    }
+
+    /// Check if the set contains the given identifier type
+    pub fn contains(&self, ty: IdentifierType) -> bool {
+        self.bits & (1 << ty as u16) != 0
+    }
+
+    /// Check if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// The number of identifier types in the set
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Iterate over the identifier types in the set, in the order of the `IdentifierType` enum
+    pub fn iter(&self) -> impl Iterator<Item = IdentifierType> {
+        let set = *self;
+        IDENTIFIER_TYPES
+            .iter()
+            .cloned()
+            .filter(move |&ty| set.contains(ty))
+    }
+}
+
+impl From<IdentifierType> for IdentifierTypeSet {
+    fn from(ty: IdentifierType) -> Self {
+        let mut set = IdentifierTypeSet::new();
+        set.insert(ty);
+        set
+    }
+}
+
+impl FromIterator<IdentifierType> for IdentifierTypeSet {
+    fn from_iter<I: IntoIterator<Item = IdentifierType>>(iter: I) -> Self {
+        let mut set = IdentifierTypeSet::new();
+        for ty in iter {
+            set.insert(ty);
+        }
+        set
+    }
+}
+
 /// Methods for determining characters not restricted from use for identifiers.
 pub trait GeneralSecurityProfile {
     /// Returns whether the character is not restricted from use for identifiers.
     fn identifier_allowed(self) -> bool;
 
     /// Returns the [identifier type](https://www.unicode.org/reports/tr39/#Identifier_Status_and_Type)
+    ///
+    /// If the character has several identifier types, this is the first one listed
+    /// in the Unicode data; use [`identifier_types`](Self::identifier_types) to get all of them.
     fn identifier_type(self) -> Option<IdentifierType>;
+
+    /// Returns all the [identifier types](https://www.unicode.org/reports/tr39/#Identifier_Status_and_Type)
+    ///
+    /// The default implementation only returns the type given by [`identifier_type`](Self::identifier_type).
+    fn identifier_types(self) -> IdentifierTypeSet
+    where
+        Self: Sized,
+    {
+        self.identifier_type().into_iter().collect()
+    }
 }
 
 impl GeneralSecurityProfile for char {
@@ -23,4 +127,186 @@ impl GeneralSecurityProfile for char {
     fn identifier_type(self) -> Option<IdentifierType> {
         identifier::identifier_type(self)
     }
+    #[inline]
+    fn identifier_types(self) -> IdentifierTypeSet {
+        identifier::identifier_types(self)
+            .iter()
+            .map(|&(_, _, ty)| ty)
+            .collect()
+    }
+}
+
+/// The identifier types allowed by the General Security Profile
+#[cfg(feature = "alloc")]
+const DEFAULT_ALLOWED_TYPES: IdentifierTypeSet = IdentifierTypeSet {
+    bits: 1 << IdentifierType::Inclusion as u16 | 1 << IdentifierType::Recommended as u16,
+};
+
+/// The identifier types given to characters because of their script,
+/// which [`IdentifierProfile::include_script`] waives
+#[cfg(feature = "alloc")]
+const SCRIPT_BASED_TYPES: IdentifierTypeSet = IdentifierTypeSet {
+    bits: 1 << IdentifierType::Limited_Use as u16 | 1 << IdentifierType::Exclusion as u16,
+};
+
+/// A sorted list of disjoint character ranges
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug)]
+struct CharRanges {
+    ranges: Vec<(char, char)>,
+}
+
+#[cfg(feature = "alloc")]
+impl CharRanges {
+    fn insert(&mut self, lo: char, hi: char) {
+        if lo > hi {
+            return;
+        }
+        let (mut lo, mut hi) = (lo, hi);
+        // Merge with every range that overlaps or touches the new one
+        let start = self
+            .ranges
+            .partition_point(|&(_, r_hi)| (r_hi as u32) + 1 < lo as u32);
+        let end = self
+            .ranges
+            .partition_point(|&(r_lo, _)| r_lo as u32 <= hi as u32 + 1);
+        if start < end {
+            lo = lo.min(self.ranges[start].0);
+            hi = hi.max(self.ranges[end - 1].1);
+        }
+        self.ranges.splice(start..end, core::iter::once((lo, hi)));
+    }
+
+    fn contains(&self, c: char) -> bool {
+        let i = self.ranges.partition_point(|&(_, hi)| hi < c);
+        matches!(self.ranges.get(i), Some(&(lo, _)) if lo <= c)
+    }
+}
+
+/// A customized identifier profile, built on top of the
+/// [General Security Profile](https://www.unicode.org/reports/tr39/#General_Security_Profile)
+///
+/// A character is checked against the profile in this order:
+///
+/// 1. characters in an excluded range are not allowed;
+/// 2. characters in an included range are allowed;
+/// 3. characters whose `Script` is excluded are not allowed;
+/// 4. otherwise, a character is allowed if all of its identifier types are allowed.
+///    For characters whose `Script` is included, the script-based types
+///    `Limited_Use` and `Exclusion` count as allowed too.
+///
+/// By default only the `Inclusion` and `Recommended` types are allowed, which
+/// matches [`GeneralSecurityProfile::identifier_allowed`].
+///
+/// ```rust
+/// use unicode_security::general_security_profile::{IdentifierProfile, IdentifierType};
+/// use unicode_script::Script;
+///
+/// let profile = IdentifierProfile::new()
+///     .include_script(Script::Cherokee)
+///     .exclude_type(IdentifierType::Inclusion)
+///     .include_range('$', '$');
+/// assert!(profile.allows_str("ᏣᎳᎩ"));
+/// assert!(profile.allows_str("$x"));
+/// assert!(!profile.allows('-'));
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct IdentifierProfile {
+    allowed_types: IdentifierTypeSet,
+    included_scripts: ScriptBitSet,
+    excluded_scripts: ScriptBitSet,
+    included_ranges: CharRanges,
+    excluded_ranges: CharRanges,
+}
+
+#[cfg(feature = "alloc")]
+impl Default for IdentifierProfile {
+    fn default() -> Self {
+        IdentifierProfile {
+            allowed_types: DEFAULT_ALLOWED_TYPES,
+            included_scripts: ScriptBitSet::default(),
+            excluded_scripts: ScriptBitSet::default(),
+            included_ranges: CharRanges::default(),
+            excluded_ranges: CharRanges::default(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl IdentifierProfile {
+    /// Create a profile equivalent to the General Security Profile
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow characters of the given identifier type
+    pub fn include_type(mut self, ty: IdentifierType) -> Self {
+        self.allowed_types.insert(ty);
+        self
+    }
+
+    /// Disallow characters of the given identifier type
+    pub fn exclude_type(mut self, ty: IdentifierType) -> Self {
+        self.allowed_types.remove(ty);
+        self
+    }
+
+    /// Allow the `Limited_Use` and `Exclusion` characters of the given script
+    pub fn include_script(mut self, script: Script) -> Self {
+        self.included_scripts.insert(script);
+        self.excluded_scripts.remove(script);
+        self
+    }
+
+    /// Disallow all characters of the given script
+    pub fn exclude_script(mut self, script: Script) -> Self {
+        self.excluded_scripts.insert(script);
+        self.included_scripts.remove(script);
+        self
+    }
+
+    /// Allow all characters in the range `lo..=hi`, unless they are in an excluded range
+    pub fn include_range(mut self, lo: char, hi: char) -> Self {
+        self.included_ranges.insert(lo, hi);
+        self
+    }
+
+    /// Disallow all characters in the range `lo..=hi`
+    pub fn exclude_range(mut self, lo: char, hi: char) -> Self {
+        self.excluded_ranges.insert(lo, hi);
+        self
+    }
+
+    /// The identifier types allowed by this profile
+    pub fn allowed_types(&self) -> IdentifierTypeSet {
+        self.allowed_types
+    }
+
+    /// Check if the character is allowed in identifiers by this profile
+    pub fn allows(&self, c: char) -> bool {
+        if self.excluded_ranges.contains(c) {
+            return false;
+        }
+        if self.included_ranges.contains(c) {
+            return true;
+        }
+        let mut allowed_types = self.allowed_types;
+        if !self.included_scripts.is_empty() || !self.excluded_scripts.is_empty() {
+            let script = c.script();
+            if self.excluded_scripts.contains(script) {
+                return false;
+            }
+            if self.included_scripts.contains(script) {
+                allowed_types.bits |= SCRIPT_BASED_TYPES.bits;
+            }
+        }
+        let types = identifier::identifier_types(c);
+        !types.is_empty() && types.iter().all(|&(_, _, ty)| allowed_types.contains(ty))
+    }
+
+    /// Check if every character of the string is allowed in identifiers by this profile
+    pub fn allows_str(&self, s: &str) -> bool {
+        s.chars().all(|c| self.allows(c))
+    }
 }
@@ -31,6 +31,18 @@
 //! unicode-security supports a `no_std` feature. This eliminates dependence
 //! on std, and instead uses equivalent functions from core.
 //!
+//! The `alloc` feature, which is not enabled by default, provides types which need
+//! dynamic memory allocation, such as the `ConfusableIndex` of the `confusable_index`
+//! module, the `IdentifierProfile` of the `general_security_profile` module, the
+//! `source_code` checks and the `lookalike` generator.
+//!
+//! The `domain` feature, which implies `alloc`, provides the domain name checks of the
+//! `domain` module, along with the UTS #46 mapping table they need.
+//!
+//! The `bidi` feature, which implies `alloc`, provides `bidi_skeleton` and
+//! `is_bidi_confusable` in the `confusable_detection` module, which depend on
+//! the `unicode-bidi` and `unicode-bidi-mirroring` crates.
+//!
 //! # crates.io
 //!
 //! You can use this package in your project by adding the following
@@ -53,24 +65,51 @@
 #[macro_use]
 extern crate std;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[cfg(feature = "bench")]
 extern crate test;
 
 pub use tables::UNICODE_VERSION;
 
+#[cfg(feature = "bidi")]
+mod bidi;
 pub mod confusable_detection;
+#[cfg(feature = "alloc")]
+pub mod confusable_index;
+#[cfg(feature = "domain")]
+pub mod domain;
+pub mod email;
 pub mod general_security_profile;
+#[cfg(feature = "alloc")]
+pub mod lookalike;
+pub mod mixed_number;
 pub mod mixed_script;
+pub mod optional_detection;
 pub mod restriction_level;
+pub mod security_report;
+#[cfg(feature = "alloc")]
+pub mod source_code;
+pub mod uax31;
 
-pub use confusable_detection::skeleton;
+pub use confusable_detection::{skeleton, Confusable};
 pub use general_security_profile::GeneralSecurityProfile;
+pub use mixed_number::MixedNumbers;
 pub use mixed_script::is_potential_mixed_script_confusable_char;
 pub use mixed_script::MixedScript;
-pub use restriction_level::{RestrictionLevel, RestrictionLevelDetection};
+pub use optional_detection::OptionalDetection;
+pub use restriction_level::{
+    DetailedRestrictionLevelDetection, RestrictionLevel, RestrictionLevelDetection,
+    RestrictionLevelReport,
+};
+pub use security_report::{analyze, SecurityReport};
 
 #[rustfmt::skip]
 pub(crate) mod tables;
 
 #[cfg(test)]
 mod tests;
+
+#[cfg(all(test, feature = "bench"))]
+mod benches;
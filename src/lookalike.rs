@@ -0,0 +1,369 @@
+//! Generation of [confusable](https://www.unicode.org/reports/tr39/#def-confusable) spellings
+//! of a name, for registering defensive names or building test corpora
+//!
+//! This module requires the `alloc` feature.
+
+use crate::confusable_detection::{confusables_of, confusables_of_str, skeleton_eq};
+use crate::mixed_script::{AugmentedScript, AugmentedScriptSet};
+use crate::GeneralSecurityProfile;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use unicode_script::Script;
+
+/// A confusable spelling of a name, found by [`LookalikeGenerator::generate`]
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Lookalike {
+    /// The confusable spelling
+    pub name: String,
+    /// The number of characters or sequences of characters of the original name
+    /// which were substituted
+    pub substitutions: usize,
+    /// The [resolved script set](https://www.unicode.org/reports/tr39/#def-resolved-script-set)
+    /// of the spelling, empty if it is mixed-script
+    pub scripts: AugmentedScriptSet,
+}
+
+impl Lookalike {
+    /// Check if the spelling is [single-script](https://www.unicode.org/reports/tr39/#def-single-script)
+    pub fn is_single_script(&self) -> bool {
+        !self.scripts.is_empty()
+    }
+}
+
+/// The longest sequence of characters of a name which may be substituted at once, like `rn` by `m`
+const MAX_SEQUENCE_LEN: usize = 2;
+
+/// A possible substitution of the characters of a name from byte `start` to `end`
+#[derive(Clone, Debug)]
+struct Substitution {
+    start: usize,
+    end: usize,
+    replacement: &'static str,
+    scripts: AugmentedScriptSet,
+}
+
+/// Enumerates the confusable spellings of a name, made by substituting characters or short
+/// sequences of characters with others which have the same prototype in `confusables.txt`
+///
+/// The single-script spellings come first, then the mixed-script ones; within each group,
+/// spellings with fewer substitutions come first. The enumeration stops after a maximum number
+/// of substitutions and a maximum number of spellings, since there are usually far too many
+/// of them: `paypal` alone has millions. It also stops after visiting a maximum number of partial
+/// spellings, so that long names with many substitutions take a bounded time.
+#[derive(Copy, Clone, Debug)]
+pub struct LookalikeGenerator {
+    max_substitutions: usize,
+    max_lookalikes: usize,
+    max_visits: usize,
+    identifier_allowed_only: bool,
+}
+
+impl Default for LookalikeGenerator {
+    fn default() -> Self {
+        LookalikeGenerator {
+            max_substitutions: 2,
+            max_lookalikes: 100,
+            max_visits: 1_000_000,
+            identifier_allowed_only: true,
+        }
+    }
+}
+
+impl LookalikeGenerator {
+    /// Create a generator for up to 100 spellings with up to 2 substitutions,
+    /// using only characters [allowed in identifiers](GeneralSecurityProfile::identifier_allowed)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of substitutions in a spelling
+    pub fn max_substitutions(mut self, max: usize) -> Self {
+        self.max_substitutions = max;
+        self
+    }
+
+    /// Set the maximum number of spellings generated
+    pub fn max_lookalikes(mut self, max: usize) -> Self {
+        self.max_lookalikes = max;
+        self
+    }
+
+    /// Set the maximum number of partial spellings visited, one million by default
+    ///
+    /// Once it is reached, [`generate`](Self::generate) returns the spellings found so far.
+    pub fn max_visits(mut self, max: usize) -> Self {
+        self.max_visits = max;
+        self
+    }
+
+    /// Set whether substitutions may use characters restricted from use in identifiers,
+    /// such as symbols and compatibility characters
+    pub fn allow_restricted(mut self, allow: bool) -> Self {
+        self.identifier_allowed_only = !allow;
+        self
+    }
+
+    /// Enumerate the confusable spellings of a name, which is meant to be ASCII
+    ///
+    /// The name itself is not included.
+    pub fn generate(&self, name: &str) -> Vec<Lookalike> {
+        let substitutions = self.substitutions(name);
+
+        // Every script a single-script spelling could be in; Common stands for the spellings
+        // with only Common and Inherited characters
+        let mut targets = vec![Script::Common];
+        let sets = name.chars().map(AugmentedScriptSet::for_char).chain(
+            substitutions
+                .iter()
+                .map(|substitution| substitution.scripts),
+        );
+        for set in sets.filter(|set| !set.is_all()) {
+            for member in set.iter() {
+                if let AugmentedScript::Script(script) = member {
+                    if !targets.contains(&script) {
+                        targets.push(script);
+                    }
+                }
+            }
+        }
+
+        // The resolved script set of the rest of the name from each byte
+        let mut suffix_sets = vec![AugmentedScriptSet::default(); name.len() + 1];
+        for (pos, c) in name.char_indices().rev() {
+            let mut set = suffix_sets[pos + c.len_utf8()];
+            set.intersect_with(c.into());
+            suffix_sets[pos] = set;
+        }
+
+        let mut search = Search {
+            name,
+            substitutions: &substitutions,
+            suffix_sets: &suffix_sets,
+            max: self.max_lookalikes,
+            visits_left: self.max_visits,
+            seen: BTreeSet::new(),
+            found: Vec::new(),
+            buf: String::with_capacity(name.len() * 2),
+            stack: Vec::new(),
+        };
+        for count in 1..=self.max_substitutions {
+            for &script in &targets {
+                search.run(count, Some(script));
+            }
+        }
+        for count in 1..=self.max_substitutions {
+            search.run(count, None);
+        }
+        search.found
+    }
+
+    /// Find all the possible substitutions in a name, sorted by start
+    fn substitutions(&self, name: &str) -> Vec<Substitution> {
+        let mut substitutions = Vec::new();
+        for (start, _) in name.char_indices() {
+            let ends = name[start..]
+                .char_indices()
+                .skip(1)
+                .map(|(len, _)| start + len)
+                .chain(Some(name.len()))
+                .take(MAX_SEQUENCE_LEN);
+            for end in ends {
+                let sequence = &name[start..end];
+                let mut chars = sequence.chars();
+                let replacements = match (chars.next(), chars.next()) {
+                    (Some(c), None) => confusables_of(c),
+                    _ => confusables_of_str(sequence),
+                };
+                for replacement in replacements {
+                    if self.identifier_allowed_only
+                        && !replacement.chars().all(|c| c.identifier_allowed())
+                    {
+                        continue;
+                    }
+                    substitutions.push(Substitution {
+                        start,
+                        end,
+                        replacement,
+                        scripts: AugmentedScriptSet::for_str(replacement),
+                    });
+                }
+            }
+        }
+        substitutions
+    }
+}
+
+/// The state of the depth-first enumeration of the spellings of a name
+///
+/// The enumeration keeps its own stack of partial spellings rather than recursing, since its
+/// depth is the length of the name.
+struct Search<'a> {
+    name: &'a str,
+    substitutions: &'a [Substitution],
+    /// The resolved script set of the rest of the name from each byte
+    suffix_sets: &'a [AugmentedScriptSet],
+    max: usize,
+    visits_left: usize,
+    seen: BTreeSet<String>,
+    found: Vec<Lookalike>,
+    /// The spelling of the name up to the current position
+    buf: String,
+    stack: Vec<Frame>,
+}
+
+/// A partial spelling of the name on the stack of a [`Search`]
+#[derive(Copy, Clone)]
+struct Frame {
+    /// The byte of the name the spelling extends to
+    pos: usize,
+    /// The number of substitutions still to be made
+    remaining: usize,
+    /// The resolved script set of the spelling
+    set: AugmentedScriptSet,
+    /// The length of the spelling in `Search::buf`
+    len: usize,
+    /// The first substitution from `pos`
+    first: usize,
+    /// The next way to extend the spelling: 0 keeps the next character of the name,
+    /// `i` makes the `i`th substitution from `pos`
+    next: usize,
+}
+
+impl Search<'_> {
+    /// Enumerate the spellings with exactly `count` substitutions, keeping only characters
+    /// in the given script, if any, or else only the spellings which can still become
+    /// mixed-script
+    fn run(&mut self, count: usize, script: Option<Script>) {
+        self.buf.clear();
+        self.visit(0, count, count, script, AugmentedScriptSet::default());
+        while let Some(frame) = self.stack.last_mut() {
+            if self.found.len() >= self.max || self.visits_left == 0 {
+                self.stack.clear();
+                break;
+            }
+            let choice = frame.next;
+            frame.next += 1;
+            let frame = *frame;
+            self.buf.truncate(frame.len);
+
+            if choice == 0 {
+                let c = self.name[frame.pos..].chars().next().unwrap();
+                let fits = match script {
+                    Some(script) => AugmentedScriptSet::for_char(c).contains(script),
+                    None => true,
+                };
+                if fits {
+                    let mut set = frame.set;
+                    set.intersect_with(c.into());
+                    self.buf.push(c);
+                    self.visit(
+                        frame.pos + c.len_utf8(),
+                        frame.remaining,
+                        count,
+                        script,
+                        set,
+                    );
+                }
+                continue;
+            }
+
+            let substitution = match self.substitutions.get(frame.first + choice - 1) {
+                Some(substitution) if frame.remaining > 0 && substitution.start == frame.pos => {
+                    substitution
+                }
+                _ => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            if let Some(script) = script {
+                if !substitution.scripts.contains(script) {
+                    continue;
+                }
+            }
+            let mut set = frame.set;
+            set.intersect_with(substitution.scripts);
+            self.buf.push_str(substitution.replacement);
+            self.visit(substitution.end, frame.remaining - 1, count, script, set);
+        }
+    }
+
+    /// Visit the current spelling, which extends to byte `pos` of the name and is to be
+    /// extended with exactly `remaining` more substitutions, recording it if it is complete
+    /// and pushing it on the stack otherwise
+    ///
+    /// `set` is the resolved script set of the current spelling.
+    fn visit(
+        &mut self,
+        pos: usize,
+        remaining: usize,
+        count: usize,
+        script: Option<Script>,
+        set: AugmentedScriptSet,
+    ) {
+        if self.found.len() >= self.max || self.visits_left == 0 {
+            return;
+        }
+        self.visits_left -= 1;
+        if pos == self.name.len() {
+            if remaining == 0 {
+                self.record(count, script.is_some(), set);
+            }
+            return;
+        }
+        let first = self.substitutions.partition_point(|s| s.start < pos);
+        if script.is_none() && !self.can_become_mixed(pos, first, remaining, set) {
+            return;
+        }
+        self.stack.push(Frame {
+            pos,
+            remaining,
+            set,
+            len: self.buf.len(),
+            first,
+            next: 0,
+        });
+    }
+
+    /// Check if a spelling with the resolved script set `set` up to byte `pos` of the name
+    /// can still become mixed-script, where `first` is the first substitution from `pos`
+    ///
+    /// Substitutions only replace characters of the rest of the name, so they can only
+    /// make the set of the rest larger, unless one of them leaves `set`.
+    fn can_become_mixed(
+        &self,
+        pos: usize,
+        first: usize,
+        remaining: usize,
+        set: AugmentedScriptSet,
+    ) -> bool {
+        let mut rest = set;
+        rest.intersect_with(self.suffix_sets[pos]);
+        if rest.is_empty() {
+            return true;
+        }
+        remaining > 0
+            && self.substitutions[first..].iter().any(|substitution| {
+                let mut narrowed = set;
+                narrowed.intersect_with(substitution.scripts);
+                narrowed != set
+            })
+    }
+
+    fn record(&mut self, substitutions: usize, single_script: bool, scripts: AugmentedScriptSet) {
+        if scripts.is_empty() == single_script
+            || self.seen.contains(&self.buf)
+            || !skeleton_eq(&self.buf, self.name)
+        {
+            return;
+        }
+        self.seen.insert(self.buf.clone());
+        self.found.push(Lookalike {
+            name: self.buf.clone(),
+            substitutions,
+            scripts,
+        });
+    }
+}
@@ -0,0 +1,85 @@
+//! [Mixed-number detection](https://www.unicode.org/reports/tr39/#Mixed_Number_Detection)
+
+use crate::tables::decimal_digit::{decimal_digit_zero_index, DECIMAL_DIGIT_ZEROS};
+
+/// Find the zero of the set of decimal digits (General_Category=Nd) a character belongs to
+///
+/// Returns `None` if the character is not a decimal digit.
+pub fn digit_zero(c: char) -> Option<char> {
+    decimal_digit_zero_index(c).map(|idx| DECIMAL_DIGIT_ZEROS[idx])
+}
+
+/// A set of zeros of decimal digit sets, as used by a string
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+pub struct DigitZeros {
+    bits: u128,
+}
+
+impl DigitZeros {
+    /// Add the digit set of a character to this set, if the character is a decimal digit
+    pub fn insert_digit(&mut self, c: char) {
+        if let Some(idx) = decimal_digit_zero_index(c) {
+            self.bits |= 1 << idx;
+        }
+    }
+
+    /// Check if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// The number of distinct digit sets in this set
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// Check if the set contains the digit set of the given digit
+    pub fn contains_digit(&self, c: char) -> bool {
+        match decimal_digit_zero_index(c) {
+            Some(idx) => self.bits & (1 << idx) != 0,
+            None => false,
+        }
+    }
+
+    /// Iterate over the zeros in this set, in code point order
+    pub fn iter(&self) -> impl Iterator<Item = char> {
+        let bits = self.bits;
+        DECIMAL_DIGIT_ZEROS
+            .iter()
+            .enumerate()
+            .filter(move |&(idx, _)| bits & (1 << idx) != 0)
+            .map(|(_, &zero)| zero)
+    }
+}
+
+/// Extension trait for [mixed-number detection](https://www.unicode.org/reports/tr39/#Mixed_Number_Detection)
+pub trait MixedNumbers {
+    /// Check if a string contains decimal digits from more than one set of digits,
+    /// i.e. digits with different zeros
+    fn has_mixed_numbers(self) -> bool;
+
+    /// Find the zeros of the decimal digit sets used in a string
+    fn digit_zeros(self) -> DigitZeros;
+}
+
+impl MixedNumbers for &'_ str {
+    fn has_mixed_numbers(self) -> bool {
+        let mut zero = None;
+        for idx in self.chars().filter_map(decimal_digit_zero_index) {
+            match zero {
+                None => zero = Some(idx),
+                Some(zero) if zero != idx => return true,
+                Some(_) => {}
+            }
+        }
+        false
+    }
+
+    fn digit_zeros(self) -> DigitZeros {
+        let mut zeros = DigitZeros::default();
+        for ch in self.chars() {
+            zeros.insert_digit(ch);
+        }
+        zeros
+    }
+}
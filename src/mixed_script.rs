@@ -1,11 +1,90 @@
 //! [Mixed-script detection](https://www.unicode.org/reports/tr39/#Mixed_Script_Detection)
 
+use crate::confusable_detection::skeleton_eq;
+use crate::tables::script_list::{extension_scripts, SCRIPTS};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::ops::Range;
+use core::slice;
+use core::str::CharIndices;
 use unicode_script::{Script, ScriptExtension};
 
+/// A set of scripts, indexed by the `Script` enum
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub(crate) struct ScriptBitSet {
+    bits: [u64; 4],
+}
+
+impl ScriptBitSet {
+    pub(crate) fn insert(&mut self, script: Script) {
+        let i = script as usize;
+        self.bits[i / 64] |= 1 << (i % 64);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn remove(&mut self, script: Script) {
+        let i = script as usize;
+        self.bits[i / 64] &= !(1 << (i % 64));
+    }
+
+    pub(crate) fn contains(&self, script: Script) -> bool {
+        let i = script as usize;
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bits == [0; 4]
+    }
+
+    fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterate over the `Script` discriminants of the members of the set
+    fn indices(self) -> impl Iterator<Item = usize> {
+        (0..self.bits.len()).flat_map(move |i| {
+            let mut word = self.bits[i];
+            core::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    fn intersect_with(&mut self, other: Self) {
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word &= other;
+        }
+    }
+
+    fn union_with(&mut self, other: Self) {
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= other;
+        }
+    }
+
+    fn difference_with(&mut self, other: Self) {
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word &= !other;
+        }
+    }
+}
+
 /// An Augmented script set, as defined by UTS 39
 ///
 /// https://www.unicode.org/reports/tr39/#def-augmented-script-set
-#[derive(Copy, Clone, PartialEq, Debug, Hash, Eq)]
+///
+/// Its `Display` implementation lists the ISO 15924 codes of its members, like `{Cyrl, Latn}`,
+/// or shows `ALL` for the set of all scripts.
+#[derive(Copy, Clone, Debug)]
 pub struct AugmentedScriptSet {
     /// The base ScriptExtension value
     pub base: ScriptExtension,
@@ -15,6 +94,8 @@ pub struct AugmentedScriptSet {
     pub jpan: bool,
     /// Korean
     pub kore: bool,
+    /// The scripts added by [`union_with`](Self::union_with) which are not in `base`
+    extra: ScriptBitSet,
 }
 
 impl From<ScriptExtension> for AugmentedScriptSet {
@@ -48,6 +129,7 @@ impl From<ScriptExtension> for AugmentedScriptSet {
             hanb,
             jpan,
             kore,
+            extra: ScriptBitSet::default(),
         }
     }
 }
@@ -71,22 +153,114 @@ impl Default for AugmentedScriptSet {
             hanb: true,
             jpan: true,
             kore: true,
+            extra: ScriptBitSet::default(),
         }
     }
 }
 
+// Sets with the same members are equal, however their scripts are split between `base` and `extra`
+impl PartialEq for AugmentedScriptSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_all() == other.is_all()
+            && self.scripts() == other.scripts()
+            && self.hanb == other.hanb
+            && self.jpan == other.jpan
+            && self.kore == other.kore
+    }
+}
+
+impl Eq for AugmentedScriptSet {}
+
+impl Hash for AugmentedScriptSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.is_all().hash(state);
+        self.scripts().hash(state);
+        self.hanb.hash(state);
+        self.jpan.hash(state);
+        self.kore.hash(state);
+    }
+}
+
+/// The scripts of a ScriptExtension value, not counting Common and Inherited
+fn extension_script_set(ext: ScriptExtension) -> ScriptBitSet {
+    let mut scripts = ScriptBitSet::default();
+    match ext {
+        ScriptExtension::Single(Script::Common)
+        | ScriptExtension::Single(Script::Inherited)
+        | ScriptExtension::Single(Script::Unknown) => {}
+        ScriptExtension::Single(script) => scripts.insert(script),
+        _ => match extension_scripts(ext) {
+            Some(list) => {
+                for &script in list {
+                    scripts.insert(script);
+                }
+            }
+            // Only an intersection of the values of the table
+            None => {
+                for &script in SCRIPTS {
+                    if ext.contains_script(script) {
+                        scripts.insert(script);
+                    }
+                }
+            }
+        },
+    }
+    scripts
+}
+
 impl AugmentedScriptSet {
     /// Intersect this set with another
     pub fn intersect_with(&mut self, other: Self) {
+        if !self.extra.is_empty() || !other.extra.is_empty() {
+            if other.is_all() {
+                // Nothing to intersect but the pseudo-scripts
+            } else if self.is_all() {
+                self.extra = other.extra;
+            } else {
+                let mut scripts = self.scripts();
+                scripts.intersect_with(other.scripts());
+                self.extra = scripts;
+            }
+        }
         self.base.intersect_with(other.base);
+        if !self.extra.is_empty() {
+            self.extra.difference_with(extension_script_set(self.base));
+        }
         self.hanb = self.hanb && other.hanb;
         self.jpan = self.jpan && other.jpan;
         self.kore = self.kore && other.kore;
     }
 
+    /// Add the members of another set to this set
+    ///
+    /// When one of the sets has all the scripts of the other, `base` becomes the value of that set.
+    /// Otherwise, such as when no `ScriptExtension` value has exactly the scripts of the union,
+    /// `base` keeps its value and the scripts of `other` it lacks are kept separately: the set
+    /// then has more scripts than `base`.
+    pub fn union_with(&mut self, other: Self) {
+        if other.is_all() {
+            self.base = other.base;
+            self.extra = ScriptBitSet::default();
+        } else if !self.is_all() {
+            let scripts = self.scripts();
+            let mut union = scripts;
+            union.union_with(other.scripts());
+            if union == other.scripts() {
+                self.base = other.base;
+                self.extra = other.extra;
+            } else if union != scripts {
+                self.extra = union;
+                self.extra.difference_with(extension_script_set(self.base));
+            }
+        }
+        self.hanb = self.hanb || other.hanb;
+        self.jpan = self.jpan || other.jpan;
+        self.kore = self.kore || other.kore;
+    }
+
     /// Check if the set is empty
     pub fn is_empty(&self) -> bool {
-        self.base.is_empty() && !self.hanb && !self.jpan && !self.kore
+        self.base.is_empty() && self.extra.is_empty() && !self.hanb && !self.jpan && !self.kore
     }
 
     /// Check if the set is "All" (Common or Inherited)
@@ -95,6 +269,67 @@ impl AugmentedScriptSet {
             || self.base == ScriptExtension::Single(Script::Inherited)
     }
 
+    /// Check if the set contains a script
+    ///
+    /// The set "All" contains every script but Unknown.
+    pub fn contains(&self, script: Script) -> bool {
+        if script == Script::Unknown {
+            false
+        } else {
+            self.is_all() || self.base.contains_script(script) || self.extra.contains(script)
+        }
+    }
+
+    /// Iterate over the members of the set: its scripts in the order of their ISO 15924 codes,
+    /// then its pseudo-scripts
+    ///
+    /// The set "All" yields every script but Common, Inherited and Unknown.
+    pub fn iter(&self) -> AugmentedScripts {
+        AugmentedScripts {
+            set: *self,
+            scripts: SCRIPTS.iter(),
+            pseudo: 0,
+        }
+    }
+
+    /// The number of members of the set, as yielded by [`iter`](Self::iter)
+    pub fn len(&self) -> usize {
+        let scripts = if self.is_all() {
+            SCRIPTS.len()
+        } else {
+            self.scripts().len()
+        };
+        scripts + self.hanb as usize + self.jpan as usize + self.kore as usize
+    }
+
+    /// The scripts of the set, not counting the pseudo-scripts; empty if the set is "All"
+    fn scripts(&self) -> ScriptBitSet {
+        if self.is_all() {
+            return ScriptBitSet::default();
+        }
+        let mut scripts = extension_script_set(self.base);
+        scripts.union_with(self.extra);
+        scripts
+    }
+
+    /// The only script of the set, if it has exactly one script and no pseudo-script
+    pub(crate) fn single_script(&self) -> Option<Script> {
+        let scripts = self.scripts();
+        if self.is_all() || self.hanb || self.jpan || self.kore || scripts.len() != 1 {
+            return None;
+        }
+        SCRIPTS
+            .iter()
+            .cloned()
+            .find(|&script| scripts.contains(script))
+    }
+
+    /// Iterate over the `Script` discriminants of the scripts of the set,
+    /// which is nothing if the set is "All"
+    pub(crate) fn script_indices(&self) -> impl Iterator<Item = usize> {
+        self.scripts().indices()
+    }
+
     /// Construct an AugmentedScriptSet for a given character
     pub fn for_char(c: char) -> Self {
         ScriptExtension::from(c).into()
@@ -110,11 +345,181 @@ impl AugmentedScriptSet {
     }
 }
 
+impl fmt::Display for AugmentedScriptSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_all() {
+            return f.write_str("ALL");
+        }
+        f.write_str("{")?;
+        for (i, script) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", script)?;
+        }
+        f.write_str("}")
+    }
+}
+
+/// A member of an [`AugmentedScriptSet`]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum AugmentedScript {
+    /// A script
+    Script(Script),
+    /// Han with Bopomofo
+    Hanb,
+    /// Japanese
+    Jpan,
+    /// Korean
+    Kore,
+}
+
+impl AugmentedScript {
+    /// The ISO 15924 code of the script
+    pub fn short_name(self) -> &'static str {
+        match self {
+            AugmentedScript::Script(script) => script.short_name(),
+            AugmentedScript::Hanb => "Hanb",
+            AugmentedScript::Jpan => "Jpan",
+            AugmentedScript::Kore => "Kore",
+        }
+    }
+}
+
+impl fmt::Display for AugmentedScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.short_name())
+    }
+}
+
+/// An iterator over the members of an [`AugmentedScriptSet`].
+///
+/// This is created by [`AugmentedScriptSet::iter`].
+#[derive(Clone, Debug)]
+pub struct AugmentedScripts {
+    set: AugmentedScriptSet,
+    scripts: slice::Iter<'static, Script>,
+    /// The number of pseudo-scripts already considered
+    pseudo: usize,
+}
+
+impl Iterator for AugmentedScripts {
+    type Item = AugmentedScript;
+
+    fn next(&mut self) -> Option<AugmentedScript> {
+        let set = self.set;
+        if let Some(&script) = self.scripts.find(|&&script| set.contains(script)) {
+            return Some(AugmentedScript::Script(script));
+        }
+        let pseudo = [
+            (set.hanb, AugmentedScript::Hanb),
+            (set.jpan, AugmentedScript::Jpan),
+            (set.kore, AugmentedScript::Kore),
+        ];
+        while let Some(&(present, script)) = pseudo.get(self.pseudo) {
+            self.pseudo += 1;
+            if present {
+                return Some(script);
+            }
+        }
+        None
+    }
+}
+
+/// Finds the [resolved script set](https://www.unicode.org/reports/tr39/#def-resolved-script-set)
+/// of a string fed one character at a time, such as an identifier being lexed or typed
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+pub struct ScriptSetAccumulator {
+    set: AugmentedScriptSet,
+    /// The length in bytes of the characters pushed so far
+    len: usize,
+    first_mixed: Option<(usize, char)>,
+}
+
+impl ScriptSetAccumulator {
+    /// Create an accumulator for the empty string
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a character to the end of the string
+    ///
+    /// Returns whether the string is still [single-script](https://www.unicode.org/reports/tr39/#def-single-script).
+    pub fn push(&mut self, c: char) -> bool {
+        if self.first_mixed.is_none() {
+            self.set.intersect_with(c.into());
+            if self.set.is_empty() {
+                self.first_mixed = Some((self.len, c));
+            }
+        }
+        self.len += c.len_utf8();
+        self.first_mixed.is_none()
+    }
+
+    /// Add all the characters of a string to the end of the string
+    ///
+    /// Returns whether the string is still [single-script](https://www.unicode.org/reports/tr39/#def-single-script).
+    pub fn push_str(&mut self, s: &str) -> bool {
+        for c in s.chars() {
+            self.push(c);
+        }
+        self.first_mixed.is_none()
+    }
+
+    /// The resolved script set of the characters pushed so far
+    pub fn resolved_set(&self) -> AugmentedScriptSet {
+        self.set
+    }
+
+    /// Check if the characters pushed so far are [single-script](https://www.unicode.org/reports/tr39/#def-single-script)
+    pub fn is_single_script(&self) -> bool {
+        self.first_mixed.is_none()
+    }
+
+    /// The byte offset and the character which first left the resolved script set empty,
+    /// if any
+    pub fn first_mixed(&self) -> Option<(usize, char)> {
+        self.first_mixed
+    }
+
+    /// The length in bytes of the characters pushed so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if no character was pushed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Go back to the empty string
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl Extend<char> for ScriptSetAccumulator {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl FromIterator<char> for ScriptSetAccumulator {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut acc = ScriptSetAccumulator::new();
+        acc.extend(iter);
+        acc
+    }
+}
+
 /// Extension trait for [mixed-script detection](https://www.unicode.org/reports/tr39/#Mixed_Script_Detection)
 pub trait MixedScript {
     /// Check if a string is [single-script](https://www.unicode.org/reports/tr39/#def-single-script)
     ///
     /// Note that a single-script string may still contain multiple Script properties!
+    #[allow(clippy::wrong_self_convention)]
     fn is_single_script(self) -> bool;
 
     /// Find the [resolved script set](https://www.unicode.org/reports/tr39/#def-resolved-script-set) of a given string
@@ -144,3 +549,127 @@ pub fn is_potential_mixed_script_confusable_char(c: char) -> bool {
 
     potential_mixed_script_confusable(c)
 }
+
+/// The script sets involved in a pair of [mixed-script confusable](https://www.unicode.org/reports/tr39/#def-mixed-script-confusables)
+/// strings
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct MixedScriptConfusable {
+    /// The resolved script set of the first string
+    pub first: AugmentedScriptSet,
+    /// The resolved script set of the second string
+    pub second: AugmentedScriptSet,
+}
+
+/// Check if two strings are [mixed-script confusable](https://www.unicode.org/reports/tr39/#def-mixed-script-confusables)
+///
+/// Two strings are mixed-script confusable if they have the same [skeleton](crate::skeleton)
+/// and their resolved script sets are disjoint. Returns the resolved script sets of both strings
+/// if that is the case.
+pub fn mixed_script_confusable(a: &str, b: &str) -> Option<MixedScriptConfusable> {
+    let first = AugmentedScriptSet::for_str(a);
+    let second = AugmentedScriptSet::for_str(b);
+    let mut common = first;
+    common.intersect_with(second);
+    if !common.is_empty() || !skeleton_eq(a, b) {
+        return None;
+    }
+    Some(MixedScriptConfusable { first, second })
+}
+
+/// A character which may make a string [mixed-script confusable](https://www.unicode.org/reports/tr39/#def-mixed-script-confusables)
+/// with another string
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct PotentialMixedScriptConfusable {
+    /// The byte offset of the character in the string
+    pub index: usize,
+    /// The character itself
+    pub ch: char,
+    /// The script set of the character
+    pub scripts: AugmentedScriptSet,
+    /// The scripts containing a character with the same prototype,
+    /// other than the script of the character itself
+    pub confusable_scripts: &'static [Script],
+}
+
+/// Find the characters that may make a string [mixed-script confusable](https://www.unicode.org/reports/tr39/#def-mixed-script-confusables)
+/// with another string
+///
+/// See [`is_potential_mixed_script_confusable_char`] for the caveats on characters
+/// restricted from use for identifiers.
+pub fn potential_mixed_script_confusables(s: &str) -> PotentialMixedScriptConfusables<'_> {
+    PotentialMixedScriptConfusables {
+        inner: s.char_indices(),
+    }
+}
+
+/// An iterator over the potential mixed-script confusable characters of a string.
+///
+/// This is created by [`potential_mixed_script_confusables`].
+#[derive(Clone, Debug)]
+pub struct PotentialMixedScriptConfusables<'a> {
+    inner: CharIndices<'a>,
+}
+
+impl Iterator for PotentialMixedScriptConfusables<'_> {
+    type Item = PotentialMixedScriptConfusable;
+
+    fn next(&mut self) -> Option<PotentialMixedScriptConfusable> {
+        use crate::tables::whole_script_confusable::whole_script_confusable_targets;
+
+        let (index, ch) = self
+            .inner
+            .by_ref()
+            .find(|&(_, ch)| is_potential_mixed_script_confusable_char(ch))?;
+        Some(PotentialMixedScriptConfusable {
+            index,
+            ch,
+            scripts: ch.into(),
+            confusable_scripts: whole_script_confusable_targets(ch),
+        })
+    }
+}
+
+/// Split a string into runs of characters with a common script
+///
+/// Each run is as long as possible while its [resolved script set](https://www.unicode.org/reports/tr39/#def-resolved-script-set)
+/// is not empty, so the augmented scripts keep Han and Hiragana together as Japanese, for instance.
+/// Common and Inherited characters join the run before them, or the first run at the start
+/// of the string; a string with only such characters is a single run. A
+/// [single-script](https://www.unicode.org/reports/tr39/#def-single-script) string has at most one run.
+pub fn script_runs(s: &str) -> ScriptRuns<'_> {
+    ScriptRuns {
+        inner: s.char_indices(),
+        pending: None,
+    }
+}
+
+/// An iterator over the script runs of a string, with their byte ranges and resolved script sets.
+///
+/// This is created by [`script_runs`].
+#[derive(Clone, Debug)]
+pub struct ScriptRuns<'a> {
+    inner: CharIndices<'a>,
+    /// The character which ended the previous run
+    pending: Option<(usize, char)>,
+}
+
+impl Iterator for ScriptRuns<'_> {
+    type Item = (Range<usize>, AugmentedScriptSet);
+
+    fn next(&mut self) -> Option<(Range<usize>, AugmentedScriptSet)> {
+        let (start, ch) = self.pending.take().or_else(|| self.inner.next())?;
+        let mut set = AugmentedScriptSet::for_char(ch);
+        let mut end = start + ch.len_utf8();
+        for (index, ch) in self.inner.by_ref() {
+            let mut next = set;
+            next.intersect_with(ch.into());
+            if next.is_empty() {
+                self.pending = Some((index, ch));
+                break;
+            }
+            set = next;
+            end = index + ch.len_utf8();
+        }
+        Some((start..end, set))
+    }
+}
@@ -0,0 +1,190 @@
+//! [Optional detection](https://www.unicode.org/reports/tr39/#Optional_Detection)
+//! of nonspacing mark sequences which can hide or duplicate diacritics
+
+use crate::tables::nonspacing_mark::{is_nonspacing_mark, is_soft_dotted};
+use core::str::{CharIndices, Chars};
+use unicode_normalization::char::{canonical_combining_class, decompose_canonical};
+use unicode_normalization::{Decompositions, UnicodeNormalization};
+
+/// The maximum number of consecutive nonspacing marks suggested by UTS 39
+pub const DEFAULT_MAX_NONSPACING_MARKS: usize = 4;
+
+/// U+0307 COMBINING DOT ABOVE
+const COMBINING_DOT_ABOVE: char = '\u{307}';
+
+/// A suspicious sequence of nonspacing marks
+///
+/// Nonspacing marks are characters with General_Category Mn or Me, whatever their
+/// canonical combining class. All checks are done on the NFD form of each sequence of
+/// a character followed by nonspacing marks, and `index` is the byte offset of the
+/// sequence in the original string.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum NonspacingMarkIssue {
+    /// The same nonspacing mark occurs twice in a row
+    RepeatedMark {
+        /// Byte offset of the combining character sequence
+        index: usize,
+        /// The repeated mark
+        mark: char,
+    },
+    /// More than the allowed number of nonspacing marks occur in a row
+    TooManyMarks {
+        /// Byte offset of the combining character sequence
+        index: usize,
+        /// The number of marks in a row, up to the first one exceeding the limit
+        count: usize,
+    },
+    /// A nonspacing mark is hidden by the base character,
+    /// such as U+0307 COMBINING DOT ABOVE on a soft-dotted character like `i`
+    HiddenMark {
+        /// Byte offset of the combining character sequence
+        index: usize,
+        /// The base character
+        base: char,
+        /// The hidden mark
+        mark: char,
+    },
+}
+
+/// Find suspicious sequences of nonspacing marks in a string, allowing at most
+/// `max_marks` nonspacing marks in a row
+pub fn nonspacing_mark_issues(s: &str, max_marks: usize) -> NonspacingMarkIssues<'_> {
+    NonspacingMarkIssues {
+        s,
+        chars: s.char_indices().peekable(),
+        sequence: None,
+        max_marks,
+        soft_dotted_base: None,
+        above_mark_seen: false,
+        last_mark: None,
+        marks: 0,
+    }
+}
+
+/// An iterator over the suspicious nonspacing mark sequences of a string.
+///
+/// This is created by [`nonspacing_mark_issues`].
+#[derive(Clone)]
+pub struct NonspacingMarkIssues<'a> {
+    s: &'a str,
+    chars: core::iter::Peekable<CharIndices<'a>>,
+    /// The byte offset and NFD form of the current combining character sequence
+    sequence: Option<(usize, Decompositions<Chars<'a>>)>,
+    max_marks: usize,
+    soft_dotted_base: Option<char>,
+    above_mark_seen: bool,
+    last_mark: Option<char>,
+    marks: usize,
+}
+
+/// Check if a character starts a new sequence of nonspacing marks in NFD
+///
+/// Marks with a canonical combining class of 0, like most Indic vowel signs
+/// and U+034F COMBINING GRAPHEME JOINER, do not start a new sequence.
+fn starts_sequence(c: char) -> bool {
+    let mut first = None;
+    decompose_canonical(c, |d| {
+        first.get_or_insert(d);
+    });
+    !matches!(first, Some(d) if is_nonspacing_mark(d))
+}
+
+impl NonspacingMarkIssues<'_> {
+    /// Move on to the next combining character sequence, returning false at the end of the string
+    fn next_sequence(&mut self) -> bool {
+        let start = match self.chars.next() {
+            Some((start, _)) => start,
+            None => return false,
+        };
+        let mut end = self.s.len();
+        while let Some(&(idx, ch)) = self.chars.peek() {
+            if starts_sequence(ch) {
+                end = idx;
+                break;
+            }
+            self.chars.next();
+        }
+        self.sequence = Some((start, self.s[start..end].chars().nfd()));
+        self.soft_dotted_base = None;
+        self.above_mark_seen = false;
+        self.last_mark = None;
+        self.marks = 0;
+        true
+    }
+}
+
+impl Iterator for NonspacingMarkIssues<'_> {
+    type Item = NonspacingMarkIssue;
+
+    fn next(&mut self) -> Option<NonspacingMarkIssue> {
+        loop {
+            let (index, ch) = match &mut self.sequence {
+                Some((index, chars)) => match chars.next() {
+                    Some(ch) => (*index, ch),
+                    None => {
+                        self.sequence = None;
+                        continue;
+                    }
+                },
+                None => {
+                    if self.next_sequence() {
+                        continue;
+                    }
+                    return None;
+                }
+            };
+
+            if !is_nonspacing_mark(ch) {
+                self.soft_dotted_base = Some(ch).filter(|&ch| is_soft_dotted(ch));
+                self.above_mark_seen = false;
+                self.last_mark = None;
+                self.marks = 0;
+                continue;
+            }
+
+            self.marks += 1;
+            let repeated = self.last_mark == Some(ch);
+            self.last_mark = Some(ch);
+            let hidden = match self.soft_dotted_base {
+                Some(base) if !self.above_mark_seen && ch == COMBINING_DOT_ABOVE => Some(base),
+                _ => None,
+            };
+            if canonical_combining_class(ch) == 230 {
+                self.above_mark_seen = true;
+            }
+
+            if let Some(base) = hidden {
+                return Some(NonspacingMarkIssue::HiddenMark {
+                    index,
+                    base,
+                    mark: ch,
+                });
+            }
+            if repeated {
+                return Some(NonspacingMarkIssue::RepeatedMark { index, mark: ch });
+            }
+            if self.marks == self.max_marks + 1 {
+                return Some(NonspacingMarkIssue::TooManyMarks {
+                    index,
+                    count: self.marks,
+                });
+            }
+        }
+    }
+}
+
+/// Extension trait for the [optional detection](https://www.unicode.org/reports/tr39/#Optional_Detection)
+/// of nonspacing mark sequences
+pub trait OptionalDetection {
+    /// Check if a string contains suspicious nonspacing mark sequences, allowing at most
+    /// [`DEFAULT_MAX_NONSPACING_MARKS`] nonspacing marks in a row
+    fn has_suspicious_nonspacing_marks(self) -> bool;
+}
+
+impl OptionalDetection for &'_ str {
+    fn has_suspicious_nonspacing_marks(self) -> bool {
+        nonspacing_mark_issues(self, DEFAULT_MAX_NONSPACING_MARKS)
+            .next()
+            .is_some()
+    }
+}
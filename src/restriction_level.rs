@@ -3,7 +3,8 @@
 
 use crate::mixed_script::AugmentedScriptSet;
 use crate::GeneralSecurityProfile;
-use unicode_script::{Script, ScriptExtension};
+use core::fmt;
+use unicode_script::Script;
 
 #[derive(Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Debug, Hash)]
 /// The [Restriction level](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection)
@@ -23,6 +24,34 @@ pub enum RestrictionLevel {
     Unrestricted,
 }
 
+impl RestrictionLevel {
+    /// The next stricter level, if any
+    pub(crate) fn stricter(self) -> Option<RestrictionLevel> {
+        use RestrictionLevel::*;
+        match self {
+            ASCIIOnly => None,
+            SingleScript => Some(ASCIIOnly),
+            HighlyRestrictive => Some(SingleScript),
+            ModeratelyRestrictive => Some(HighlyRestrictive),
+            MinimallyRestrictive => Some(ModeratelyRestrictive),
+            Unrestricted => Some(MinimallyRestrictive),
+        }
+    }
+}
+
+impl fmt::Display for RestrictionLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RestrictionLevel::ASCIIOnly => "ASCII-only",
+            RestrictionLevel::SingleScript => "single script",
+            RestrictionLevel::HighlyRestrictive => "highly restrictive",
+            RestrictionLevel::ModeratelyRestrictive => "moderately restrictive",
+            RestrictionLevel::MinimallyRestrictive => "minimally restrictive",
+            RestrictionLevel::Unrestricted => "unrestricted",
+        })
+    }
+}
+
 /// Utilities for determining which [restriction level](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection)
 /// a string satisfies
 pub trait RestrictionLevelDetection: Sized {
@@ -39,36 +68,139 @@ pub trait RestrictionLevelDetection: Sized {
     }
 }
 
+/// Utilities for explaining which [restriction level](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection)
+/// a string satisfies
+pub trait DetailedRestrictionLevelDetection: Sized {
+    /// Detect the [restriction level](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection),
+    /// along with the resolved script set and the characters responsible for exceeding each level
+    ///
+    /// This will _not_ check identifier well-formedness, as different applications may have different notions of well-formedness
+    fn detect_restriction_level_detailed(self) -> RestrictionLevelReport;
+}
+
 impl RestrictionLevelDetection for &'_ str {
     fn detect_restriction_level(self) -> RestrictionLevel {
-        let mut ascii_only = true;
-        let mut set = AugmentedScriptSet::default();
-        let mut exclude_latin_set = AugmentedScriptSet::default();
+        let mut state = RestrictionLevelState::default();
         for ch in self.chars() {
-            if !GeneralSecurityProfile::identifier_allowed(ch) {
+            state.push(ch);
+            if !state.allowed {
                 return RestrictionLevel::Unrestricted;
             }
-            if ch.is_ascii() {
-                ascii_only = false;
-            }
-            let ch_set = ch.into();
-            set.intersect_with(ch_set);
-            if !ch_set.base.contains_script(Script::Latin) {
-                exclude_latin_set.intersect_with(ch_set);
+        }
+        state.level()
+    }
+}
+
+impl DetailedRestrictionLevelDetection for &'_ str {
+    fn detect_restriction_level_detailed(self) -> RestrictionLevelReport {
+        restriction_level_report(self.char_indices())
+    }
+}
+
+/// Build a [`RestrictionLevelReport`] from characters and their byte offsets
+pub(crate) fn restriction_level_report(
+    chars: impl Iterator<Item = (usize, char)>,
+) -> RestrictionLevelReport {
+    let mut state = RestrictionLevelState::default();
+    let mut violations = [None; 5];
+    for (idx, ch) in chars {
+        state.push(ch);
+        // The level may go down as well as up, so only the violations
+        // which last until the end of the string are kept
+        let level = state.level() as usize;
+        for (i, violation) in violations.iter_mut().enumerate() {
+            if i >= level {
+                *violation = None;
+            } else if violation.is_none() {
+                *violation = Some((idx, ch));
             }
         }
+    }
+    RestrictionLevelReport {
+        level: state.level(),
+        script_set: state.set,
+        violations,
+    }
+}
+
+/// A detailed report on the [restriction level](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection)
+/// of a string
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct RestrictionLevelReport {
+    /// The restriction level of the string
+    pub level: RestrictionLevel,
+    /// The [resolved script set](https://www.unicode.org/reports/tr39/#def-resolved-script-set) of the string
+    pub script_set: AugmentedScriptSet,
+    /// The violation of each level below `Unrestricted`
+    violations: [Option<(usize, char)>; 5],
+}
+
+impl RestrictionLevelReport {
+    /// Find the byte index and the character from which the string stops satisfying the given level,
+    /// i.e. the longest prefix of the string which satisfies the level is followed by that character
+    ///
+    /// Adding characters can also lower the level of a string, as in `"a\u{64B}\u{628}"` where
+    /// the Arabic letter narrows down the scripts of the preceding mark, so prefixes which
+    /// do not satisfy the level may be followed by prefixes which do.
+    ///
+    /// Returns `None` if the string satisfies the level.
+    pub fn first_violation(&self, level: RestrictionLevel) -> Option<(usize, char)> {
+        self.violations.get(level as usize).cloned().flatten()
+    }
+}
+
+/// The restriction level of a string, computed one character at a time
+#[derive(Copy, Clone, Debug)]
+struct RestrictionLevelState {
+    allowed: bool,
+    ascii_only: bool,
+    set: AugmentedScriptSet,
+    exclude_latin_set: AugmentedScriptSet,
+}
+
+impl Default for RestrictionLevelState {
+    fn default() -> Self {
+        RestrictionLevelState {
+            allowed: true,
+            ascii_only: true,
+            set: AugmentedScriptSet::default(),
+            exclude_latin_set: AugmentedScriptSet::default(),
+        }
+    }
+}
+
+impl RestrictionLevelState {
+    fn push(&mut self, ch: char) {
+        if !GeneralSecurityProfile::identifier_allowed(ch) {
+            self.allowed = false;
+        }
+        if !ch.is_ascii() {
+            self.ascii_only = false;
+        }
+        let ch_set = ch.into();
+        self.set.intersect_with(ch_set);
+        if !ch_set.contains(Script::Latin) {
+            self.exclude_latin_set.intersect_with(ch_set);
+        }
+    }
 
-        if ascii_only {
+    fn level(&self) -> RestrictionLevel {
+        if !self.allowed {
+            return RestrictionLevel::Unrestricted;
+        } else if self.ascii_only {
             return RestrictionLevel::ASCIIOnly;
-        } else if !set.is_empty() {
+        } else if !self.set.is_empty() {
             return RestrictionLevel::SingleScript;
-        } else if exclude_latin_set.kore || exclude_latin_set.hanb || exclude_latin_set.jpan {
+        } else if self.exclude_latin_set.kore
+            || self.exclude_latin_set.hanb
+            || self.exclude_latin_set.jpan
+        {
             return RestrictionLevel::HighlyRestrictive;
-        } else if let ScriptExtension::Single(script) = exclude_latin_set.base {
+        } else if let Some(script) = self.exclude_latin_set.single_script() {
             if script.is_recommended() && script != Script::Cyrillic && script != Script::Greek {
                 return RestrictionLevel::ModeratelyRestrictive;
             }
         }
-        return RestrictionLevel::MinimallyRestrictive;
+        RestrictionLevel::MinimallyRestrictive
     }
 }
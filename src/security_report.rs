@@ -0,0 +1,219 @@
+//! A combined report on all the [security checks](https://www.unicode.org/reports/tr39/)
+//! of a string, explaining why a check fails
+
+use crate::general_security_profile::IdentifierTypeSet;
+use crate::mixed_script::{
+    is_potential_mixed_script_confusable_char, potential_mixed_script_confusables,
+    AugmentedScriptSet, PotentialMixedScriptConfusable, PotentialMixedScriptConfusables,
+};
+use crate::restriction_level::{
+    DetailedRestrictionLevelDetection, RestrictionLevel, RestrictionLevelReport,
+};
+use crate::GeneralSecurityProfile;
+use core::fmt;
+use core::str::CharIndices;
+
+/// Analyze a string with all the checks of this crate
+///
+/// The report on the whole string is computed up front; the reports on
+/// individual characters are computed when iterating over them.
+pub fn analyze(s: &str) -> SecurityReport<'_> {
+    SecurityReport {
+        s,
+        restriction: s.detect_restriction_level_detailed(),
+    }
+}
+
+/// The result of [`analyze`]
+///
+/// Its `Display` implementation lists the problems found, one per line,
+/// in a form suitable for compiler diagnostics.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct SecurityReport<'a> {
+    s: &'a str,
+    /// The restriction level of the string, and the characters responsible for it
+    pub restriction: RestrictionLevelReport,
+}
+
+impl<'a> SecurityReport<'a> {
+    /// The analyzed string
+    pub fn as_str(&self) -> &'a str {
+        self.s
+    }
+
+    /// The [restriction level](https://www.unicode.org/reports/tr39/#Restriction_Level_Detection) of the string
+    pub fn restriction_level(&self) -> RestrictionLevel {
+        self.restriction.level
+    }
+
+    /// The [resolved script set](https://www.unicode.org/reports/tr39/#def-resolved-script-set) of the string
+    pub fn script_set(&self) -> AugmentedScriptSet {
+        self.restriction.script_set
+    }
+
+    /// Check if all characters are allowed in identifiers
+    pub fn identifier_allowed(&self) -> bool {
+        self.restriction.level != RestrictionLevel::Unrestricted
+    }
+
+    /// Iterate over the reports on each character of the string
+    pub fn chars(&self) -> CharReports<'a> {
+        CharReports {
+            inner: self.s.char_indices(),
+        }
+    }
+
+    /// Iterate over the characters not allowed in identifiers
+    pub fn disallowed_chars(&self) -> impl Iterator<Item = CharReport> + 'a {
+        self.chars().filter(|report| !report.identifier_allowed)
+    }
+
+    /// Iterate over the characters which likely make the string
+    /// [mixed-script confusable](https://www.unicode.org/reports/tr39/#def-mixed-script-confusables)
+    /// with another string
+    ///
+    /// UTS #39 only defines which strings are mixed-script confusable, not which of their
+    /// characters are to blame. As a policy of this crate, these are the
+    /// [potential mixed-script confusables](crate::is_potential_mixed_script_confusable_char)
+    /// of a single script which are confusable with some other script used by at least as many
+    /// characters of the string, like the Cyrillic `а` in `pаypal`. Characters of Common and
+    /// Inherited are not counted, and single-script strings have none.
+    pub fn mixed_script_confusables(&self) -> MixedScriptConfusables<'a> {
+        let mut script_counts = [0; SCRIPT_COUNTS];
+        for ch in self.s.chars() {
+            for i in AugmentedScriptSet::for_char(ch).script_indices() {
+                script_counts[i] += 1;
+            }
+        }
+        MixedScriptConfusables {
+            script_counts,
+            inner: potential_mixed_script_confusables(self.s),
+        }
+    }
+}
+
+/// The analysis of a single character of a string
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct CharReport {
+    /// The byte offset of the character in the string
+    pub index: usize,
+    /// The character itself
+    pub ch: char,
+    /// Whether the character is allowed in identifiers
+    pub identifier_allowed: bool,
+    /// The [identifier types](https://www.unicode.org/reports/tr39/#Identifier_Status_and_Type) of the character
+    pub identifier_types: IdentifierTypeSet,
+    /// The script set of the character
+    pub scripts: AugmentedScriptSet,
+    /// Whether the character is a [potential mixed-script confusable](crate::is_potential_mixed_script_confusable_char)
+    pub potential_mixed_script_confusable: bool,
+}
+
+/// An iterator over the reports on each character of a string.
+///
+/// This is created by [`SecurityReport::chars`].
+#[derive(Clone, Debug)]
+pub struct CharReports<'a> {
+    inner: CharIndices<'a>,
+}
+
+impl Iterator for CharReports<'_> {
+    type Item = CharReport;
+
+    fn next(&mut self) -> Option<CharReport> {
+        let (index, ch) = self.inner.next()?;
+        Some(CharReport {
+            index,
+            ch,
+            identifier_allowed: ch.identifier_allowed(),
+            identifier_types: ch.identifier_types(),
+            scripts: ch.into(),
+            potential_mixed_script_confusable: is_potential_mixed_script_confusable_char(ch),
+        })
+    }
+}
+
+/// The number of `Script` discriminants a script set may hold
+const SCRIPT_COUNTS: usize = 256;
+
+/// An iterator over the characters which likely make a string mixed-script confusable.
+///
+/// This is created by [`SecurityReport::mixed_script_confusables`].
+#[derive(Clone, Debug)]
+pub struct MixedScriptConfusables<'a> {
+    /// The number of characters of the string with each script, ignoring Common and Inherited
+    script_counts: [usize; SCRIPT_COUNTS],
+    inner: PotentialMixedScriptConfusables<'a>,
+}
+
+impl Iterator for MixedScriptConfusables<'_> {
+    type Item = PotentialMixedScriptConfusable;
+
+    fn next(&mut self) -> Option<PotentialMixedScriptConfusable> {
+        while let Some(confusable) = self.inner.next() {
+            let script = match confusable.scripts.single_script() {
+                Some(script) => script,
+                None => continue,
+            };
+            let own_count = self.script_counts[script as usize];
+            if confusable.confusable_scripts.iter().any(|&other| {
+                let count = self.script_counts[other as usize];
+                count > 0 && own_count <= count
+            }) {
+                return Some(confusable);
+            }
+        }
+        None
+    }
+}
+
+/// Write a character the way diagnostics usually do, e.g. `'а' (U+0430)`
+fn write_char(f: &mut fmt::Formatter<'_>, ch: char) -> fmt::Result {
+    write!(f, "{:?} (U+{:04X})", ch, ch as u32)
+}
+
+impl fmt::Display for SecurityReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = self.restriction.level;
+        write!(f, "restriction level: {}", level)?;
+        let violation = level
+            .stricter()
+            .and_then(|stricter| Some((stricter, self.restriction.first_violation(stricter)?)));
+        if let Some((stricter, (index, ch))) = violation {
+            write!(f, " (not {} because of ", stricter)?;
+            write_char(f, ch)?;
+            write!(f, " at byte {})", index)?;
+        }
+        // Both kinds of issues are listed in the order of their byte offsets
+        let mut confusables = self.mixed_script_confusables().peekable();
+        for report in self.disallowed_chars() {
+            while let Some(confusable) = confusables.next_if(|c| c.index < report.index) {
+                write_confusable(f, &confusable)?;
+            }
+            write!(f, "\nbyte {}: ", report.index)?;
+            write_char(f, report.ch)?;
+            f.write_str(" is not allowed in identifiers")?;
+            let mut types = report.identifier_types.iter();
+            if let Some(ty) = types.next() {
+                write!(f, " ({:?}", ty)?;
+                for ty in types {
+                    write!(f, " {:?}", ty)?;
+                }
+                f.write_str(")")?;
+            }
+        }
+        for confusable in confusables {
+            write_confusable(f, &confusable)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_confusable(
+    f: &mut fmt::Formatter<'_>,
+    confusable: &PotentialMixedScriptConfusable,
+) -> fmt::Result {
+    write!(f, "\nbyte {}: ", confusable.index)?;
+    write_char(f, confusable.ch)?;
+    f.write_str(" may be confused with characters of other scripts")
+}
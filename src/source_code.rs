@@ -0,0 +1,219 @@
+//! [Source code spoofing](https://www.unicode.org/reports/tr55/) checks, as defined by UTS #55
+//!
+//! The caller tokenizes the source and feeds each token to a [`SourceChecker`] along with
+//! its [`TokenKind`]. Explicit bidi formatting characters must be balanced within each
+//! string literal and comment, default ignorable characters are flagged outside of them,
+//! and identifiers are compared by [skeleton](crate::skeleton) with the identifiers seen before.
+//!
+//! This module requires the `alloc` feature.
+
+use crate::confusable_index::{ConfusableIndex, ConfusableKind};
+use crate::general_security_profile::IdentifierType;
+use crate::GeneralSecurityProfile;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The kind of a source code token, which decides the checks applied to it
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum TokenKind {
+    /// An identifier, compared with all the identifiers checked before
+    Identifier,
+    /// A string or character literal, in which bidi formatting characters must be balanced
+    StringLiteral,
+    /// A comment, in which bidi formatting characters must be balanced
+    Comment,
+    /// Any other token, such as whitespace, operators or numbers
+    Other,
+}
+
+impl TokenKind {
+    /// Check if the token may contain bidi formatting characters
+    fn is_literal(self) -> bool {
+        matches!(self, TokenKind::StringLiteral | TokenKind::Comment)
+    }
+}
+
+/// A spoofing issue found in a token by [`SourceChecker::check`]
+///
+/// Byte offsets are relative to the start of the token.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub enum SourceIssue {
+    /// An embedding, override or isolate initiator is not terminated before the end
+    /// of the token or line, so it may reorder the code following it
+    UnterminatedBidiControl {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// A PDF or PDI does not terminate any embedding, override or isolate of the token
+    UnmatchedBidiControl {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// An invisible character with the `Default_Ignorable` identifier type, other than
+    /// a [bidi formatting character](is_bidi_formatting_char) in a string literal or comment
+    DefaultIgnorable {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// The identifier is [confusable](https://www.unicode.org/reports/tr39/#def-confusable)
+    /// with a different identifier checked before
+    ConfusableIdentifier {
+        /// The identifier checked before
+        other: String,
+        /// How the identifiers are confusable
+        kind: ConfusableKind,
+    },
+}
+
+impl SourceIssue {
+    /// The byte offset the issue is reported at, identifiers being reported after their characters
+    fn index(&self) -> usize {
+        match *self {
+            SourceIssue::UnterminatedBidiControl { index, .. }
+            | SourceIssue::UnmatchedBidiControl { index, .. }
+            | SourceIssue::DefaultIgnorable { index, .. } => index,
+            SourceIssue::ConfusableIdentifier { .. } => usize::MAX,
+        }
+    }
+}
+
+impl fmt::Display for SourceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceIssue::UnterminatedBidiControl { index, ch } => write!(
+                f,
+                "unterminated bidi formatting character U+{:04X} at byte {}",
+                *ch as u32, index
+            ),
+            SourceIssue::UnmatchedBidiControl { index, ch } => write!(
+                f,
+                "unmatched bidi formatting character U+{:04X} at byte {}",
+                *ch as u32, index
+            ),
+            SourceIssue::DefaultIgnorable { index, ch } => write!(
+                f,
+                "invisible character U+{:04X} at byte {}",
+                *ch as u32, index
+            ),
+            SourceIssue::ConfusableIdentifier { other, .. } => {
+                write!(f, "identifier is confusable with `{}`", other)
+            }
+        }
+    }
+}
+
+/// The bidi formatting characters which may appear in string literals and comments
+///
+/// These are the explicit formatting characters of UAX #9 and the implicit directional marks.
+pub fn is_bidi_formatting_char(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Check if a character ends a paragraph, and thus all embeddings, overrides and isolates
+fn is_paragraph_separator(ch: char) -> bool {
+    matches!(
+        ch,
+        '\n' | '\r' | '\u{1C}'..='\u{1E}' | '\u{85}' | '\u{2029}'
+    )
+}
+
+/// Find the explicit bidi formatting characters of a string which are not balanced,
+/// in the order of their byte offsets
+///
+/// As in the [Unicode Bidirectional Algorithm](https://www.unicode.org/reports/tr9/),
+/// a PDI terminates the last isolate and the embeddings and overrides opened after it,
+/// a PDF only terminates an embedding or override opened after the last isolate,
+/// and paragraph separators terminate everything. Anything left open at a paragraph
+/// separator or at the end of the string is unterminated.
+pub fn bidi_control_issues(s: &str) -> Vec<SourceIssue> {
+    let mut issues = Vec::new();
+    // Initiators still open, with whether they are isolates
+    let mut stack: Vec<(usize, char, bool)> = Vec::new();
+    let unterminated = |stack: &mut Vec<(usize, char, bool)>, issues: &mut Vec<SourceIssue>| {
+        issues.extend(
+            stack
+                .drain(..)
+                .map(|(index, ch, _)| SourceIssue::UnterminatedBidiControl { index, ch }),
+        );
+    };
+    for (index, ch) in s.char_indices() {
+        match ch {
+            '\u{202A}'..='\u{202E}' if ch != '\u{202C}' => stack.push((index, ch, false)),
+            '\u{2066}'..='\u{2068}' => stack.push((index, ch, true)),
+            '\u{202C}' => match stack.last() {
+                Some(&(_, _, false)) => {
+                    stack.pop();
+                }
+                _ => issues.push(SourceIssue::UnmatchedBidiControl { index, ch }),
+            },
+            '\u{2069}' => match stack.iter().rposition(|&(_, _, isolate)| isolate) {
+                Some(pos) => stack.truncate(pos),
+                None => issues.push(SourceIssue::UnmatchedBidiControl { index, ch }),
+            },
+            _ if is_paragraph_separator(ch) => unterminated(&mut stack, &mut issues),
+            _ => {}
+        }
+    }
+    unterminated(&mut stack, &mut issues);
+    issues.sort_by_key(SourceIssue::index);
+    issues
+}
+
+/// Checks the tokens of source code for spoofing, remembering the identifiers seen so far
+#[derive(Clone, Debug, Default)]
+pub struct SourceChecker {
+    identifiers: ConfusableIndex,
+}
+
+impl SourceChecker {
+    /// Create a checker which has not seen any identifier
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a token, returning the issues found in the order of their byte offsets,
+    /// followed by the identifiers it is confusable with
+    ///
+    /// Identifiers are remembered, so that each pair of confusable identifiers is reported once,
+    /// on the later one.
+    pub fn check(&mut self, kind: TokenKind, token: &str) -> Vec<SourceIssue> {
+        let mut issues = if kind.is_literal() {
+            bidi_control_issues(token)
+        } else {
+            Vec::new()
+        };
+        let ignorables = token.char_indices().filter(|&(_, ch)| {
+            ch.identifier_types()
+                .contains(IdentifierType::Default_Ignorable)
+                && !(kind.is_literal() && is_bidi_formatting_char(ch))
+        });
+        issues.extend(ignorables.map(|(index, ch)| SourceIssue::DefaultIgnorable { index, ch }));
+        issues.sort_by_key(SourceIssue::index);
+
+        if kind == TokenKind::Identifier {
+            issues.extend(self.identifiers.confusables_of(token).map(|confusable| {
+                SourceIssue::ConfusableIdentifier {
+                    other: confusable.name.into(),
+                    kind: confusable.kind,
+                }
+            }));
+            self.identifiers.insert(token);
+        }
+        issues
+    }
+
+    /// Iterate over the identifiers checked so far, in the order they were first seen
+    pub fn identifiers(&self) -> impl Iterator<Item = &str> + '_ {
+        self.identifiers.names()
+    }
+}
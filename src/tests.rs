@@ -8,6 +8,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![allow(clippy::bool_assert_comparison)]
+
 #[test]
 fn test_general_security_profile_identifier_allowed() {
     use crate::GeneralSecurityProfile;
@@ -70,6 +72,17 @@ fn test_confusable_detection() {
     assert_eq!(&skeleton("ﶛﶛ").collect::<String>(), "نمىنمى");
 }
 
+#[test]
+fn test_confusable_prototype() {
+    use crate::confusable_detection::confusable_prototype;
+
+    assert_eq!(confusable_prototype('ｓ'), Some("s"));
+    assert_eq!(confusable_prototype('ﶛ'), Some("نمى"));
+    assert_eq!(confusable_prototype('m'), Some("rn"));
+    assert_eq!(confusable_prototype('s'), None);
+    assert_eq!(confusable_prototype('\u{10FFFF}'), None);
+}
+
 #[test]
 fn test_potential_mixed_script_detection() {
     use crate::is_potential_mixed_script_confusable_char;
@@ -77,3 +90,1283 @@ fn test_potential_mixed_script_detection() {
     assert!(is_potential_mixed_script_confusable_char('A'));
     assert!(!is_potential_mixed_script_confusable_char('D'));
 }
+
+#[test]
+fn test_whole_script_confusable_detection() {
+    use crate::confusable_detection::{is_whole_script_confusable_with, whole_script_confusables};
+    use std::vec::Vec;
+    use unicode_script::Script;
+
+    assert!(is_whole_script_confusable_with("scope", Script::Cyrillic));
+    assert!(is_whole_script_confusable_with("ѕсоре", Script::Latin));
+    assert!(!is_whole_script_confusable_with("scope", Script::Latin));
+    assert!(!is_whole_script_confusable_with("scope", Script::Greek));
+    // Mixed-script strings are never whole-script confusable
+    assert!(!is_whole_script_confusable_with("sсope", Script::Cyrillic));
+    // Nor are strings without any script-specific characters
+    assert!(!is_whole_script_confusable_with("", Script::Latin));
+    assert!(!is_whole_script_confusable_with("0.", Script::Latin));
+    assert_eq!(
+        whole_script_confusables("scope").collect::<Vec<_>>(),
+        [Script::Cyrillic]
+    );
+    assert_eq!(whole_script_confusables("rust").count(), 0);
+}
+
+#[test]
+fn test_mixed_script_confusable_detection() {
+    use crate::mixed_script::{mixed_script_confusable, potential_mixed_script_confusables};
+    use crate::MixedScript;
+    use std::vec::Vec;
+    use unicode_script::Script;
+
+    // "scope" with a Cyrillic 'с'
+    let pair = mixed_script_confusable("scope", "sсope").unwrap();
+    assert_eq!(pair.first, "scope".resolve_script_set());
+    assert!(pair.second.is_empty());
+    // Cyrillic "ѕсоре"
+    let pair = mixed_script_confusable("scope", "ѕсоре").unwrap();
+    assert!(pair.first.contains(Script::Latin));
+    assert!(pair.second.contains(Script::Cyrillic));
+    assert!(mixed_script_confusable("scope", "scope").is_none());
+    assert!(mixed_script_confusable("scope", "slope").is_none());
+    assert!(mixed_script_confusable("scope", "ｓｃｏｐｅ").is_none());
+
+    let found = potential_mixed_script_confusables("Do").collect::<Vec<_>>();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].index, 1);
+    assert_eq!(found[0].ch, 'o');
+    assert!(found[0].confusable_scripts.contains(&Script::Cyrillic));
+    assert_eq!(potential_mixed_script_confusables("D").count(), 0);
+}
+
+#[test]
+fn test_single_script_confusable_detection() {
+    use crate::confusable_detection::is_single_script_confusable;
+    use crate::Confusable;
+
+    assert!(is_single_script_confusable("paypal", "paypaI"));
+    assert!(is_single_script_confusable("ﬁle", "file"));
+    assert!(is_single_script_confusable("ss", "ｓｓ"));
+    assert!(!is_single_script_confusable("scope", "ѕсоре"));
+    assert!(!is_single_script_confusable("scope", "sсope"));
+    assert!(!is_single_script_confusable("scope", "slope"));
+
+    assert!("scope".is_confusable_with("ѕсоре"));
+    assert!("scope".is_confusable_with("sсope"));
+    assert!(!"scope".is_confusable_with("slope"));
+    assert!("ｓｓ".is_single_script_confusable_with("ss"));
+    assert!(!"scope".is_single_script_confusable_with("ѕсоре"));
+    assert!("scope".is_mixed_script_confusable_with("ѕсоре"));
+    assert!(!"ｓｓ".is_mixed_script_confusable_with("ss"));
+}
+
+#[test]
+fn test_skeleton_comparison() {
+    use crate::confusable_detection::{skeleton_cmp, skeleton_eq};
+    use core::cmp::Ordering;
+
+    assert!(skeleton_eq("", ""));
+    assert!(skeleton_eq("scope", "scope"));
+    assert!(skeleton_eq("scope", "ѕсоре"));
+    assert!(skeleton_eq("ﶛ", "نمى"));
+    assert!(!skeleton_eq("scope", "slope"));
+    assert!(!skeleton_eq("scope", "scopes"));
+    assert_eq!(skeleton_cmp("scope", "ѕсоре"), Ordering::Equal);
+    assert_eq!(skeleton_cmp("scope", "scopes"), Ordering::Less);
+    assert_eq!(skeleton_cmp("slope", "ѕсоре"), Ordering::Greater);
+    assert_eq!(skeleton_cmp("", "a"), Ordering::Less);
+}
+
+#[test]
+fn test_skeleton_key() {
+    use crate::confusable_detection::SkeletonKey;
+    use std::collections::HashSet;
+
+    let mut names = HashSet::new();
+    assert!(names.insert(SkeletonKey("scope")));
+    assert!(names.insert(SkeletonKey("slope")));
+    assert!(!names.insert(SkeletonKey("ѕсоре")));
+    assert!(!names.insert(SkeletonKey("ｓｃｏｐｅ")));
+    assert_eq!(names.get(&SkeletonKey("sсope")).unwrap().as_str(), "scope");
+    assert_eq!(names.len(), 2);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_confusable_index() {
+    use crate::confusable_index::{ConfusableIndex, ConfusableKind};
+    use std::vec::Vec;
+
+    let mut index = ConfusableIndex::new();
+    assert!(index.is_empty());
+    for name in &["scope", "slope", "paypal", "ｓｃｏｐｅ"] {
+        assert!(index.insert(name));
+    }
+    assert!(!index.insert("scope"));
+    assert_eq!(index.len(), 4);
+    assert!(index.contains("paypal"));
+    assert!(!index.contains("paypaI"));
+
+    let matches = index.confusables_of("scope").collect::<Vec<_>>();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].name, "ｓｃｏｐｅ");
+    assert_eq!(matches[0].kind, ConfusableKind::SingleScript);
+
+    let matches = index.confusables_of("ѕсоре").collect::<Vec<_>>();
+    assert_eq!(matches.len(), 2);
+    assert!(matches
+        .iter()
+        .all(|m| m.kind == ConfusableKind::WholeScript));
+
+    let matches = index.confusables_of("sсope").collect::<Vec<_>>();
+    assert_eq!(matches.len(), 2);
+    assert!(matches
+        .iter()
+        .all(|m| m.kind == ConfusableKind::MixedScript));
+
+    let matches = index.confusables_of("paypaI").collect::<Vec<_>>();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].name, "paypal");
+    assert_eq!(index.confusables_of("rust").count(), 0);
+
+    // Force a few rehashes
+    for i in 0..100 {
+        assert!(index.insert(&std::format!("name{}", i)));
+    }
+    assert_eq!(
+        index.confusables_of("narne42").next().unwrap().name,
+        "name42"
+    );
+    assert_eq!(index.names().count(), 104);
+}
+
+#[test]
+fn test_restriction_level() {
+    use crate::{RestrictionLevel, RestrictionLevelDetection};
+
+    assert_eq!("".detect_restriction_level(), RestrictionLevel::ASCIIOnly);
+    assert_eq!(
+        "rust_2018".detect_restriction_level(),
+        RestrictionLevel::ASCIIOnly
+    );
+    assert_eq!(
+        "café".detect_restriction_level(),
+        RestrictionLevel::SingleScript
+    );
+    assert_eq!(
+        "αβγ".detect_restriction_level(),
+        RestrictionLevel::SingleScript
+    );
+    assert_eq!(
+        "冬の雪".detect_restriction_level(),
+        RestrictionLevel::SingleScript
+    );
+    assert_eq!(
+        "abc中".detect_restriction_level(),
+        RestrictionLevel::HighlyRestrictive
+    );
+    assert_eq!(
+        "abcب".detect_restriction_level(),
+        RestrictionLevel::ModeratelyRestrictive
+    );
+    assert_eq!(
+        "abcд".detect_restriction_level(),
+        RestrictionLevel::MinimallyRestrictive
+    );
+    assert_eq!(
+        "abcα".detect_restriction_level(),
+        RestrictionLevel::MinimallyRestrictive
+    );
+    assert_eq!(
+        "ab\u{0}".detect_restriction_level(),
+        RestrictionLevel::Unrestricted
+    );
+    assert_eq!(
+        "µ".detect_restriction_level(),
+        RestrictionLevel::Unrestricted
+    );
+
+    assert!("abcд".check_restriction_level(RestrictionLevel::MinimallyRestrictive));
+    assert!(!"abcд".check_restriction_level(RestrictionLevel::ModeratelyRestrictive));
+}
+
+#[test]
+fn test_restriction_level_detailed() {
+    use crate::{DetailedRestrictionLevelDetection, RestrictionLevel};
+    use unicode_script::Script;
+
+    let report = "abc".detect_restriction_level_detailed();
+    assert_eq!(report.level, RestrictionLevel::ASCIIOnly);
+    assert!(report.script_set.contains(Script::Latin));
+    assert_eq!(report.first_violation(RestrictionLevel::ASCIIOnly), None);
+
+    let report = "aé中b".detect_restriction_level_detailed();
+    assert_eq!(report.level, RestrictionLevel::HighlyRestrictive);
+    assert!(report.script_set.is_empty());
+    assert_eq!(
+        report.first_violation(RestrictionLevel::ASCIIOnly),
+        Some((1, 'é'))
+    );
+    assert_eq!(
+        report.first_violation(RestrictionLevel::SingleScript),
+        Some((3, '中'))
+    );
+    assert_eq!(
+        report.first_violation(RestrictionLevel::HighlyRestrictive),
+        None
+    );
+
+    let report = "aбµ".detect_restriction_level_detailed();
+    assert_eq!(report.level, RestrictionLevel::Unrestricted);
+    assert_eq!(
+        report.first_violation(RestrictionLevel::ASCIIOnly),
+        Some((1, 'б'))
+    );
+    assert_eq!(
+        report.first_violation(RestrictionLevel::SingleScript),
+        Some((1, 'б'))
+    );
+    assert_eq!(
+        report.first_violation(RestrictionLevel::ModeratelyRestrictive),
+        Some((1, 'б'))
+    );
+    assert_eq!(
+        report.first_violation(RestrictionLevel::MinimallyRestrictive),
+        Some((3, 'µ'))
+    );
+    assert_eq!(report.first_violation(RestrictionLevel::Unrestricted), None);
+
+    // The Arabic letter lowers the level reached by the mark before it
+    let report = "a\u{64B}\u{628}".detect_restriction_level_detailed();
+    assert_eq!(report.level, RestrictionLevel::ModeratelyRestrictive);
+    assert_eq!(
+        report.first_violation(RestrictionLevel::ModeratelyRestrictive),
+        None
+    );
+    assert_eq!(
+        report.first_violation(RestrictionLevel::HighlyRestrictive),
+        Some((1, '\u{64B}'))
+    );
+}
+
+#[test]
+fn test_mixed_numbers() {
+    use crate::mixed_number::digit_zero;
+    use crate::MixedNumbers;
+    use std::vec::Vec;
+
+    assert_eq!(digit_zero('7'), Some('0'));
+    assert_eq!(digit_zero('১'), Some('০'));
+    // U+1D7D9 MATHEMATICAL DOUBLE-STRUCK DIGIT ONE
+    assert_eq!(digit_zero('\u{1d7d9}'), Some('\u{1d7d8}'));
+    assert_eq!(digit_zero('a'), None);
+    // U+00B9 SUPERSCRIPT ONE is not a decimal digit
+    assert_eq!(digit_zero('¹'), None);
+
+    assert!(!"".has_mixed_numbers());
+    assert!(!"abc".has_mixed_numbers());
+    assert!(!"a1b2c3".has_mixed_numbers());
+    assert!(!"১২৩".has_mixed_numbers());
+    assert!("1১".has_mixed_numbers());
+    assert!("12３".has_mixed_numbers());
+
+    assert!("abc".digit_zeros().is_empty());
+    let zeros = "٣1১2".digit_zeros();
+    assert_eq!(zeros.len(), 3);
+    assert!(zeros.contains_digit('9'));
+    assert!(!zeros.contains_digit('３'));
+    assert_eq!(zeros.iter().collect::<Vec<_>>(), ['0', '٠', '০']);
+}
+
+#[test]
+fn test_optional_detection() {
+    use crate::optional_detection::{nonspacing_mark_issues, NonspacingMarkIssue};
+    use crate::OptionalDetection;
+    use std::vec::Vec;
+
+    assert!(!"".has_suspicious_nonspacing_marks());
+    assert!(!"abc".has_suspicious_nonspacing_marks());
+    assert!(!"e\u{301}\u{302}".has_suspicious_nonspacing_marks());
+    assert!(!"é".has_suspicious_nonspacing_marks());
+    assert!(!"ı\u{307}".has_suspicious_nonspacing_marks());
+
+    assert_eq!(
+        nonspacing_mark_issues("xe\u{301}\u{301}", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::RepeatedMark {
+            index: 1,
+            mark: '\u{301}'
+        }]
+    );
+    // The precomposed form decomposes to the same sequence
+    assert_eq!(
+        nonspacing_mark_issues("xé\u{301}", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::RepeatedMark {
+            index: 1,
+            mark: '\u{301}'
+        }]
+    );
+    // Canonical reordering puts the two acute accents next to each other
+    assert!("e\u{301}\u{323}\u{301}".has_suspicious_nonspacing_marks());
+    assert_eq!(
+        nonspacing_mark_issues("a\u{300}\u{301}\u{302}\u{303}\u{304}b", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::TooManyMarks { index: 0, count: 5 }]
+    );
+    assert_eq!(
+        nonspacing_mark_issues("a\u{300}\u{301}\u{302}", 2).count(),
+        1
+    );
+    assert_eq!(
+        nonspacing_mark_issues("bi\u{307}", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::HiddenMark {
+            index: 1,
+            base: 'i',
+            mark: '\u{307}'
+        }]
+    );
+    // The dot above is visible once another above mark separates it from the base
+    assert!(!"i\u{301}\u{307}".has_suspicious_nonspacing_marks());
+
+    // Nonspacing marks with a canonical combining class of 0 are counted too
+    assert!(!"\u{915}\u{941}".has_suspicious_nonspacing_marks());
+    assert_eq!(
+        nonspacing_mark_issues("\u{915}\u{941}\u{941}", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::RepeatedMark {
+            index: 0,
+            mark: '\u{941}'
+        }]
+    );
+    assert_eq!(
+        nonspacing_mark_issues("\u{915}\u{941}\u{942}\u{943}\u{944}\u{945}\u{946}", 2)
+            .collect::<Vec<_>>(),
+        [NonspacingMarkIssue::TooManyMarks { index: 0, count: 3 }]
+    );
+    assert_eq!(
+        nonspacing_mark_issues("a\u{34F}\u{34F}", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::RepeatedMark {
+            index: 0,
+            mark: '\u{34F}'
+        }]
+    );
+    assert_eq!(
+        nonspacing_mark_issues("a\u{301}\u{34F}\u{301}\u{34F}\u{301}", 4).collect::<Vec<_>>(),
+        [NonspacingMarkIssue::TooManyMarks { index: 0, count: 5 }]
+    );
+}
+
+#[test]
+fn test_general_security_profile_identifier_types() {
+    use crate::general_security_profile::{IdentifierType, IdentifierTypeSet};
+    use crate::GeneralSecurityProfile;
+    use std::vec::Vec;
+
+    assert_eq!('A'.identifier_type(), Some(IdentifierType::Recommended));
+    assert_eq!('A'.identifier_types(), IdentifierType::Recommended.into());
+    assert_eq!(' '.identifier_type(), Some(IdentifierType::Not_XID));
+    // U+00B5 MICRO SIGN
+    assert!('µ'.identifier_types().contains(IdentifierType::Not_NFKC));
+    // U+E000 is a private use character, with no identifier type
+    assert_eq!('\u{e000}'.identifier_type(), None);
+    assert!('\u{e000}'.identifier_types().is_empty());
+
+    // U+0253 LATIN SMALL LETTER B WITH HOOK
+    let mut set = 'ɓ'.identifier_types();
+    assert_eq!(set, IdentifierType::Uncommon_Use.into());
+    set.insert(IdentifierType::Technical);
+    set.insert(IdentifierType::Technical);
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(IdentifierType::Technical));
+    assert!(!set.contains(IdentifierType::Obsolete));
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        [IdentifierType::Technical, IdentifierType::Uncommon_Use]
+    );
+    set.remove(IdentifierType::Technical);
+    assert_eq!(set, IdentifierType::Uncommon_Use.into());
+    assert_eq!(
+        [IdentifierType::Uncommon_Use, IdentifierType::Technical]
+            .iter()
+            .cloned()
+            .collect::<IdentifierTypeSet>()
+            .len(),
+        2
+    );
+    // U+018D LATIN SMALL LETTER TURNED DELTA
+    assert_eq!('\u{18d}'.identifier_type(), Some(IdentifierType::Technical));
+    assert!(!'\u{18d}'.identifier_allowed());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_identifier_profile() {
+    use crate::general_security_profile::{IdentifierProfile, IdentifierType};
+    use crate::GeneralSecurityProfile;
+    use unicode_script::Script;
+
+    let default = IdentifierProfile::new();
+    for c in (0..=0x10ffff).filter_map(core::char::from_u32) {
+        assert_eq!(default.allows(c), c.identifier_allowed(), "{:?}", c);
+    }
+    assert!(default.allows_str("rust"));
+    assert!(!default.allows_str("ᏣᎳᎩ"));
+
+    // Cherokee is a Limited_Use script
+    let profile = IdentifierProfile::new().include_script(Script::Cherokee);
+    assert!(profile.allows_str("ᏣᎳᎩ"));
+    assert!(profile.allows_str("rustᏣᎳᎩ"));
+    // Other Limited_Use scripts stay restricted
+    assert!(!profile.allows('ᐁ'));
+
+    let profile = IdentifierProfile::new()
+        .include_type(IdentifierType::Technical)
+        .exclude_script(Script::Greek);
+    // U+01C0 LATIN LETTER DENTAL CLICK is Technical
+    assert!(profile.allows('\u{1c0}'));
+    assert!(!profile.allows('α'));
+    assert!(profile.allows('a'));
+
+    let profile = IdentifierProfile::new()
+        .include_range('$', '$')
+        .include_range('0', '5')
+        .include_range('3', '9')
+        .exclude_range('7', '8');
+    assert!(profile.allows_str("$0123456"));
+    assert!(profile.allows('9'));
+    assert!(!profile.allows('7'));
+    assert!(!profile.allows('8'));
+
+    let profile = IdentifierProfile::new().exclude_type(IdentifierType::Inclusion);
+    assert!(!profile.allows('-'));
+    assert!(!profile.allows_str("a-b"));
+    assert!(profile.allows_str("ab"));
+}
+
+#[test]
+fn test_uax31_syntax() {
+    use crate::uax31::{
+        is_identifier_medial, is_identifier_start, is_joiner_allowed, is_xid_continue, is_xid_start,
+    };
+
+    assert!(is_xid_start('a'));
+    assert!(!is_xid_start('1'));
+    assert!(is_xid_continue('1'));
+    assert!(!is_xid_start('_'));
+    assert!(is_identifier_start('_'));
+    assert!(is_xid_start('ß'));
+    assert!(!is_xid_continue('-'));
+    assert!(is_identifier_medial('-'));
+    // U+00B7 MIDDLE DOT is XID_Continue as well as medial
+    assert!(is_xid_continue('·'));
+    assert!(is_identifier_medial('·'));
+
+    assert!(is_joiner_allowed("\u{915}\u{94d}\u{200d}", 6));
+    assert!(!is_joiner_allowed("\u{915}\u{94d}\u{200d}", 3));
+    assert!(!is_joiner_allowed("\u{200d}", 0));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_validate_identifier() {
+    use crate::general_security_profile::IdentifierProfile;
+    use crate::uax31::{validate_identifier, IdentifierError};
+
+    let profile = IdentifierProfile::new();
+    assert_eq!(validate_identifier("rust_lang", &profile), Ok(()));
+    assert_eq!(validate_identifier("_x", &profile), Ok(()));
+    assert_eq!(validate_identifier("foo-bar", &profile), Ok(()));
+    assert_eq!(validate_identifier("l·l", &profile), Ok(()));
+    assert_eq!(
+        validate_identifier("", &profile),
+        Err(vec![IdentifierError::Empty])
+    );
+    assert_eq!(
+        validate_identifier("1x", &profile),
+        Err(vec![IdentifierError::InvalidStart { index: 0, ch: '1' }])
+    );
+    assert_eq!(
+        validate_identifier("-a--b-", &profile),
+        Err(vec![
+            IdentifierError::MisplacedMedial { index: 0, ch: '-' },
+            IdentifierError::MisplacedMedial { index: 3, ch: '-' },
+            IdentifierError::MisplacedMedial { index: 5, ch: '-' },
+        ])
+    );
+    // '$' is valid syntax, but not allowed by the General Security Profile
+    assert_eq!(
+        validate_identifier("$x", &profile),
+        Err(vec![IdentifierError::NotAllowed { index: 0, ch: '$' }])
+    );
+    assert_eq!(
+        validate_identifier("$x", &profile.clone().include_range('$', '$')),
+        Ok(())
+    );
+    // A syntax error and a profile violation on the same character are both reported
+    assert_eq!(
+        validate_identifier("a b", &profile),
+        Err(vec![
+            IdentifierError::InvalidContinue { index: 1, ch: ' ' },
+            IdentifierError::NotAllowed { index: 1, ch: ' ' },
+        ])
+    );
+    // U+00B5 MICRO SIGN is XID_Start, but Not_NFKC
+    assert_eq!(
+        validate_identifier("xµ", &profile),
+        Err(vec![IdentifierError::NotAllowed { index: 1, ch: 'µ' }])
+    );
+    // Medials which are XID_Continue follow the usual rules
+    assert_eq!(validate_identifier("l·", &profile), Ok(()));
+    assert_eq!(validate_identifier("a··b", &profile), Ok(()));
+    assert_eq!(
+        validate_identifier("·a", &profile),
+        Err(vec![IdentifierError::InvalidStart { index: 0, ch: '·' }])
+    );
+
+    // ZWNJ breaking the cursive connection of two Arabic letters (A1)
+    assert_eq!(
+        validate_identifier("\u{628}\u{200c}\u{628}", &profile),
+        Ok(())
+    );
+    assert_eq!(
+        validate_identifier("\u{628}\u{64b}\u{200c}\u{628}", &profile),
+        Ok(())
+    );
+    // ALEF only joins to the right
+    assert_eq!(
+        validate_identifier("\u{627}\u{200c}\u{628}", &profile),
+        Err(vec![IdentifierError::MisplacedJoiner {
+            index: 2,
+            ch: '\u{200c}'
+        }])
+    );
+    // ZWNJ and ZWJ after a Devanagari virama (A2 and B)
+    assert_eq!(
+        validate_identifier("\u{915}\u{94d}\u{200c}\u{937}", &profile),
+        Ok(())
+    );
+    assert_eq!(
+        validate_identifier("\u{915}\u{94d}\u{200d}\u{937}", &profile),
+        Ok(())
+    );
+    assert_eq!(
+        validate_identifier("\u{915}\u{200d}\u{937}", &profile),
+        Err(vec![IdentifierError::MisplacedJoiner {
+            index: 3,
+            ch: '\u{200d}'
+        }])
+    );
+    assert_eq!(
+        validate_identifier("a\u{200c}b", &profile),
+        Err(vec![IdentifierError::MisplacedJoiner {
+            index: 1,
+            ch: '\u{200c}'
+        }])
+    );
+}
+
+#[test]
+fn test_security_report() {
+    use crate::general_security_profile::IdentifierType;
+    use crate::{analyze, RestrictionLevel};
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    let report = analyze("rust");
+    assert_eq!(report.restriction_level(), RestrictionLevel::ASCIIOnly);
+    assert!(report.identifier_allowed());
+    assert_eq!(report.to_string(), "restriction level: ASCII-only");
+
+    // U+0430 CYRILLIC SMALL LETTER A
+    let report = analyze("p\u{430}ypalµ");
+    assert_eq!(report.restriction_level(), RestrictionLevel::Unrestricted);
+    assert!(report.script_set().is_empty());
+    let disallowed: Vec<_> = report.disallowed_chars().collect();
+    assert_eq!(disallowed.len(), 1);
+    assert_eq!((disallowed[0].index, disallowed[0].ch), (7, 'µ'));
+    assert!(disallowed[0]
+        .identifier_types
+        .contains(IdentifierType::Not_NFKC));
+    let chars: Vec<_> = report.chars().collect();
+    assert_eq!(chars.len(), 7);
+    assert!(chars[1].potential_mixed_script_confusable);
+    let confusables: Vec<_> = report
+        .mixed_script_confusables()
+        .map(|c| (c.index, c.ch))
+        .collect();
+    assert_eq!(confusables, [(1, '\u{430}')]);
+    // U+0430 CYRILLIC SMALL LETTER A, U+0441 CYRILLIC SMALL LETTER ES
+    let confusables: Vec<_> = analyze("\u{430}b\u{441}")
+        .mixed_script_confusables()
+        .map(|c| (c.index, c.ch))
+        .collect();
+    assert_eq!(confusables, [(2, 'b')]);
+    assert_eq!(analyze("scope").mixed_script_confusables().count(), 0);
+    assert_eq!(
+        report.to_string(),
+        "restriction level: unrestricted (not minimally restrictive because of 'µ' (U+00B5) at byte 7)\n\
+         byte 1: 'а' (U+0430) may be confused with characters of other scripts\n\
+         byte 7: 'µ' (U+00B5) is not allowed in identifiers (Not_NFKC)"
+    );
+}
+
+#[test]
+fn test_email_local_part() {
+    use crate::email::{
+        is_local_part_confusable, split_subaddress, validate_local_part, EmailError,
+    };
+    use crate::RestrictionLevel;
+
+    assert_eq!(validate_local_part("john.doe"), Ok(()));
+    assert_eq!(validate_local_part("john.doe+news"), Ok(()));
+    assert_eq!(validate_local_part("o'brien!#$%&*/=?^_`{|}~-"), Ok(()));
+    assert_eq!(validate_local_part("山田.taro"), Ok(()));
+    assert_eq!(validate_local_part("δοκιμή"), Ok(()));
+    assert_eq!(validate_local_part(""), Err(EmailError::Empty));
+    assert_eq!(
+        validate_local_part(".john"),
+        Err(EmailError::MisplacedDot { index: 0 })
+    );
+    assert_eq!(
+        validate_local_part("john..doe"),
+        Err(EmailError::MisplacedDot { index: 5 })
+    );
+    assert_eq!(
+        validate_local_part("john."),
+        Err(EmailError::MisplacedDot { index: 4 })
+    );
+    assert_eq!(
+        validate_local_part("john doe"),
+        Err(EmailError::InvalidChar { index: 4, ch: ' ' })
+    );
+    // U+00B5 MICRO SIGN is not allowed in identifiers
+    assert_eq!(
+        validate_local_part("µ"),
+        Err(EmailError::InvalidChar { index: 0, ch: 'µ' })
+    );
+    // U+0430 CYRILLIC SMALL LETTER A
+    assert_eq!(
+        validate_local_part("p\u{430}ypal"),
+        Err(EmailError::RestrictionLevel {
+            level: RestrictionLevel::MinimallyRestrictive,
+            index: 1,
+            ch: '\u{430}'
+        })
+    );
+    // U+0661 ARABIC-INDIC DIGIT ONE
+    assert_eq!(
+        validate_local_part("\u{661}2"),
+        Err(EmailError::MixedNumbers)
+    );
+
+    assert_eq!(
+        split_subaddress("john+news+tech"),
+        ("john", Some("news+tech"))
+    );
+    assert_eq!(split_subaddress("john"), ("john", None));
+    assert!(is_local_part_confusable("p\u{430}ypal+x", "paypal"));
+    assert!(!is_local_part_confusable("pay.pal", "paypal"));
+}
+
+#[cfg(feature = "domain")]
+#[test]
+fn test_domain() {
+    use crate::domain::{map_domain, DomainChecker, DomainError, DomainIssue};
+    use crate::RestrictionLevel;
+
+    assert_eq!(map_domain("Example.COM.").unwrap(), "example.com");
+    // U+3002 IDEOGRAPHIC FULL STOP separates labels, U+00AD SOFT HYPHEN is ignored
+    assert_eq!(
+        map_domain("ex\u{ad}ample\u{3002}com").unwrap(),
+        "example.com"
+    );
+    assert_eq!(map_domain("xn--mnchen-3ya.de").unwrap(), "münchen.de");
+    assert_eq!(
+        map_domain("xn--80ak6aa92e.com").unwrap(),
+        "\u{430}\u{440}\u{440}\u{4cf}\u{435}.com"
+    );
+    assert_eq!(map_domain("ß.de").unwrap(), "ß.de");
+    assert_eq!(
+        map_domain("foo_bar.com"),
+        Err(DomainError::DisallowedChar { index: 3, ch: '_' })
+    );
+    assert_eq!(
+        map_domain("foo..com"),
+        Err(DomainError::EmptyLabel { label: 1 })
+    );
+    assert_eq!(
+        map_domain("xn--99.com"),
+        Err(DomainError::Punycode { label: 0 })
+    );
+    // Decoded labels must be left unchanged by the mapping, and not be ASCII only
+    assert_eq!(
+        map_domain("xn--abc-.com"),
+        Err(DomainError::Punycode { label: 0 })
+    );
+    assert_eq!(
+        map_domain("xn--mnchen-psa.de"),
+        Err(DomainError::Punycode { label: 0 })
+    );
+    assert_eq!(
+        map_domain("xn--ab-xuw.com"),
+        Err(DomainError::Punycode { label: 0 })
+    );
+
+    let mut checker = DomainChecker::new();
+    checker.protect("paypal.com").unwrap();
+    checker.protect("scope.com").unwrap();
+    checker.protect("www.scope.com").unwrap();
+
+    assert!(!checker.check("paypal.com").unwrap().is_suspicious());
+    assert!(!checker.check("пример.com").unwrap().is_suspicious());
+    assert!(!checker.check("www.münchen.de").unwrap().is_suspicious());
+
+    // U+0430 CYRILLIC SMALL LETTER A
+    let report = checker.check("www.p\u{430}ypal.com").unwrap();
+    assert_eq!(report.label(1), Some("p\u{430}ypal"));
+    assert_eq!(
+        report.issues(),
+        [
+            DomainIssue::MixedScript { label: 1 },
+            DomainIssue::RestrictionLevel {
+                label: 1,
+                level: RestrictionLevel::MinimallyRestrictive
+            },
+        ]
+    );
+
+    // A whole-script confusable, punycode encoded
+    let report = checker.check("xn--e1argc3h.com").unwrap();
+    assert_eq!(report.label(0), Some("ѕсоре"));
+    assert_eq!(
+        report.issues(),
+        [DomainIssue::Lookalike {
+            label: Some(0),
+            protected: "scope.com"
+        }]
+    );
+    let report = checker
+        .check("www.\u{455}\u{441}\u{43e}\u{440}\u{435}.com")
+        .unwrap();
+    assert!(report.issues().contains(&DomainIssue::Lookalike {
+        label: Some(1),
+        protected: "www.scope.com"
+    }));
+
+    let report = checker.check("p\u{430}ypal.com").unwrap();
+    assert!(report.issues().contains(&DomainIssue::Lookalike {
+        label: Some(0),
+        protected: "paypal.com"
+    }));
+
+    // Labels are checked one by one, so each may use a different script
+    assert!(!checker.check("пример.example.com").unwrap().is_suspicious());
+    assert!(!checker.check("www.пример.рф").unwrap().is_suspicious());
+    let report = checker.check("p\u{430}ypal.рф").unwrap();
+    assert_eq!(report.issues()[0], DomainIssue::MixedScript { label: 0 });
+    let ascii_only = checker
+        .clone()
+        .restriction_level(RestrictionLevel::ASCIIOnly);
+    let report = ascii_only.check("пример.com").unwrap();
+    assert_eq!(
+        report.issues(),
+        [DomainIssue::RestrictionLevel {
+            label: 0,
+            level: RestrictionLevel::SingleScript
+        }]
+    );
+}
+
+#[cfg(feature = "bidi")]
+#[test]
+fn test_bidi_skeleton() {
+    use crate::confusable_detection::{bidi_skeleton, is_bidi_confusable, Direction};
+    use crate::{skeleton, Confusable};
+    use std::string::String;
+
+    let ltr = Direction::LeftToRight;
+    let rtl = Direction::RightToLeft;
+
+    assert_eq!(bidi_skeleton("ѕсоре", ltr), "scope");
+    assert_eq!(bidi_skeleton("ѕсоре", rtl), "scope");
+    // Right-to-left text is displayed reversed
+    assert_eq!(
+        bidi_skeleton("\u{5d0}\u{5d1}", ltr),
+        skeleton("\u{5d1}\u{5d0}").collect::<String>()
+    );
+    // Hebrew letters followed by digits look like digits followed by the letters
+    // in a left-to-right paragraph, but not in a right-to-left one
+    assert!(is_bidi_confusable(
+        "\u{5d0}\u{5d1}12",
+        "12\u{5d0}\u{5d1}",
+        ltr
+    ));
+    assert!(!is_bidi_confusable(
+        "\u{5d0}\u{5d1}12",
+        "12\u{5d0}\u{5d1}",
+        rtl
+    ));
+    assert!(!"\u{5d0}\u{5d1}12".is_confusable_with("12\u{5d0}\u{5d1}"));
+    // Explicit formatting characters are left out after reordering
+    assert!(is_bidi_confusable("a\u{202e}bc", "acb", ltr));
+    assert!(is_bidi_confusable("a\u{202e}bc\u{202c}d", "acbd", ltr));
+    // Left-to-right text keeps its order inside a right-to-left isolate
+    assert!(is_bidi_confusable("a\u{2067}bc\u{2069}d", "abcd", ltr));
+    // Mirrored characters
+    assert_eq!(
+        bidi_skeleton("(\u{5d0})", rtl),
+        bidi_skeleton("(\u{5d0})", ltr)
+    );
+    assert_eq!(
+        bidi_skeleton("\u{5d0}<\u{5d1}", ltr),
+        skeleton("\u{5d1}>\u{5d0}").collect::<String>()
+    );
+    // Paired brackets take the direction of the text they enclose (rule N0)
+    assert_eq!(
+        bidi_skeleton("a(b)\u{5d0}", rtl),
+        skeleton("\u{5d0}a(b)").collect::<String>()
+    );
+    assert_eq!(
+        bidi_skeleton("\u{5d0}(\u{5d1})a", ltr),
+        skeleton("(\u{5d1})\u{5d0}a").collect::<String>()
+    );
+    // European numbers after Arabic letters become Arabic numbers (rule W2)
+    assert_eq!(
+        bidi_skeleton("\u{627}-12", ltr),
+        skeleton("12-\u{627}").collect::<String>()
+    );
+    // Unassigned code points of the Hebrew block are right-to-left
+    assert_eq!(
+        bidi_skeleton("\u{5ff}\u{5d0}", ltr),
+        skeleton("\u{5d0}\u{5ff}").collect::<String>()
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_source_code() {
+    use crate::confusable_index::ConfusableKind;
+    use crate::source_code::{bidi_control_issues, SourceChecker, SourceIssue, TokenKind};
+
+    // The classic "Trojan Source" comment, with an RLO and an LRI never terminated
+    assert_eq!(
+        bidi_control_issues(
+            "/* \u{202e} } \u{2066}if (admin)\u{2069} \u{2066} begin admins only */"
+        ),
+        [
+            SourceIssue::UnterminatedBidiControl {
+                index: 3,
+                ch: '\u{202e}'
+            },
+            SourceIssue::UnterminatedBidiControl {
+                index: 26,
+                ch: '\u{2066}'
+            },
+        ]
+    );
+    assert!(bidi_control_issues("\"\u{2067}abc\u{2069}\"").is_empty());
+    // PDI closes the embeddings opened inside the isolate
+    assert!(bidi_control_issues("\u{2066}\u{202b}x\u{2069}").is_empty());
+    // PDF cannot close an embedding opened outside the last isolate
+    assert_eq!(
+        bidi_control_issues("\u{202b}\u{2066}\u{202c}\u{2069}\u{202c}"),
+        [SourceIssue::UnmatchedBidiControl {
+            index: 6,
+            ch: '\u{202c}'
+        }]
+    );
+    // Line ends terminate everything
+    assert_eq!(
+        bidi_control_issues("// \u{2067}\n\u{2069}"),
+        [
+            SourceIssue::UnterminatedBidiControl {
+                index: 3,
+                ch: '\u{2067}'
+            },
+            SourceIssue::UnmatchedBidiControl {
+                index: 7,
+                ch: '\u{2069}'
+            },
+        ]
+    );
+
+    let mut checker = SourceChecker::new();
+    assert!(checker.check(TokenKind::Identifier, "scope").is_empty());
+    assert!(checker.check(TokenKind::Identifier, "scope").is_empty());
+    assert_eq!(
+        checker.check(TokenKind::Identifier, "ѕсоре"),
+        [SourceIssue::ConfusableIdentifier {
+            other: "scope".into(),
+            kind: ConfusableKind::WholeScript
+        }]
+    );
+    // Zero width space
+    assert_eq!(
+        checker.check(TokenKind::Identifier, "a\u{200b}b"),
+        [SourceIssue::DefaultIgnorable {
+            index: 1,
+            ch: '\u{200b}'
+        }]
+    );
+    // Bidi formatting characters are only allowed in literals
+    assert!(checker
+        .check(TokenKind::StringLiteral, "\"\u{200f}\"")
+        .is_empty());
+    assert_eq!(
+        checker.check(TokenKind::Other, " \u{200f}"),
+        [SourceIssue::DefaultIgnorable {
+            index: 1,
+            ch: '\u{200f}'
+        }]
+    );
+    assert_eq!(
+        checker.check(TokenKind::Comment, "// \u{2067}\u{ad}"),
+        [
+            SourceIssue::UnterminatedBidiControl {
+                index: 3,
+                ch: '\u{2067}'
+            },
+            SourceIssue::DefaultIgnorable {
+                index: 6,
+                ch: '\u{ad}'
+            },
+        ]
+    );
+    assert_eq!(checker.identifiers().count(), 3);
+}
+
+#[test]
+fn test_trie_lookups() {
+    use crate::tables::{confusable_detection, identifier};
+
+    for c in (0..=0x10FFFF).filter_map(core::char::from_u32) {
+        assert_eq!(
+            identifier::identifier_status_allowed(c),
+            identifier::identifier_status_allowed_bsearch(c)
+        );
+        assert_eq!(
+            identifier::identifier_types(c),
+            identifier::identifier_types_bsearch(c)
+        );
+        assert_eq!(
+            confusable_detection::char_confusable_prototype(c),
+            confusable_detection::char_confusable_prototype_bsearch(c)
+        );
+    }
+}
+
+#[test]
+fn test_script_set_accumulator() {
+    use crate::mixed_script::{AugmentedScriptSet, ScriptSetAccumulator};
+    use crate::MixedScript;
+
+    let mut acc = ScriptSetAccumulator::new();
+    assert!(acc.is_empty());
+    assert!(acc.resolved_set().is_all());
+    for (i, c) in "冬の雪".char_indices() {
+        assert!(acc.push(c));
+        assert_eq!(
+            acc.resolved_set(),
+            "冬の雪"[..i + c.len_utf8()].resolve_script_set()
+        );
+    }
+    // Hangul leaves no script in common with the Japanese text
+    assert!(!acc.push('은'));
+    assert_eq!(acc.first_mixed(), Some((9, '은')));
+    assert!(acc.resolved_set().is_empty());
+    assert!(!acc.push('a'));
+    assert_eq!(acc.first_mixed(), Some((9, '은')));
+    assert_eq!(acc.len(), 13);
+
+    acc.clear();
+    assert!(acc.push_str("sco"));
+    assert!(!acc.push_str("pеs"));
+    assert_eq!(acc.first_mixed(), Some((4, 'е')));
+
+    let acc: ScriptSetAccumulator = "A0".chars().collect();
+    assert!(acc.is_single_script());
+    assert_eq!(acc.resolved_set(), AugmentedScriptSet::for_str("A0"));
+}
+
+#[test]
+fn test_script_runs() {
+    use crate::mixed_script::{script_runs, AugmentedScriptSet};
+    use crate::MixedScript;
+    use std::vec::Vec;
+    use unicode_script::Script;
+
+    let runs = |s| {
+        script_runs(s)
+            .map(|(range, set)| (range.clone(), set, &s[range]))
+            .collect::<Vec<_>>()
+    };
+
+    assert!(runs("").is_empty());
+    // Common characters join the run before them, or the first one
+    let found = runs("1. pаypal");
+    assert_eq!(found.len(), 3);
+    assert_eq!(found[0].2, "1. p");
+    assert!(found[0].1.contains(Script::Latin));
+    assert_eq!(found[1].2, "а");
+    assert!(found[1].1.contains(Script::Cyrillic));
+    assert_eq!(found[2].2, "ypal");
+    assert_eq!(found[2].0, 6..10);
+    // Han and kana stay together as Japanese, but Hangul does not
+    let found = runs("夏の幻ㄒㄧㄤ");
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].2, "夏の幻");
+    assert!(found[0].1.jpan);
+    assert_eq!(found[1].2, "ㄒㄧㄤ");
+    assert!(found[1].1.hanb);
+    assert_eq!(runs("0."), [(0..2, AugmentedScriptSet::default(), "0.")]);
+    for s in ["冬の雪", "幻ㄒㄧㄤ", "日出은", "A0"].iter() {
+        assert_eq!(runs(s), [(0..s.len(), s.resolve_script_set(), *s)]);
+    }
+}
+
+#[test]
+fn test_augmented_script_set_algebra() {
+    use crate::mixed_script::{AugmentedScript, AugmentedScriptSet};
+    use std::string::ToString;
+    use std::vec::Vec;
+    use unicode_script::Script;
+
+    let latin = AugmentedScriptSet::for_char('a');
+    let cyrillic = AugmentedScriptSet::for_char('а');
+    let mut set = latin;
+    set.union_with(cyrillic);
+    assert!(set.contains(Script::Latin));
+    assert!(set.contains(Script::Cyrillic));
+    assert!(!set.contains(Script::Greek));
+    assert_eq!(set.len(), 2);
+    assert_eq!(set.to_string(), "{Cyrl, Latn}");
+    set.intersect_with(latin);
+    assert_eq!(set, latin);
+    set.intersect_with(cyrillic);
+    assert!(set.is_empty());
+    assert_eq!(set.len(), 0);
+    assert_eq!(set.to_string(), "{}");
+
+    // Han carries all the pseudo-scripts, kana only Japanese
+    let han = AugmentedScriptSet::for_char('福');
+    assert_eq!(
+        han.iter().collect::<Vec<_>>(),
+        [
+            AugmentedScript::Script(Script::Han),
+            AugmentedScript::Hanb,
+            AugmentedScript::Jpan,
+            AugmentedScript::Kore
+        ]
+    );
+    assert_eq!(AugmentedScriptSet::for_str("冬の雪").to_string(), "{Jpan}");
+    let mut set = AugmentedScriptSet::for_char('ㄒ');
+    set.union_with(AugmentedScriptSet::for_char('은'));
+    assert_eq!(set.to_string(), "{Bopo, Hang, Hanb, Kore}");
+    // Script extensions
+    assert_eq!(
+        AugmentedScriptSet::for_char('\u{0660}').to_string(),
+        "{Arab, Thaa}"
+    );
+
+    let all = AugmentedScriptSet::for_char('0');
+    assert!(all.is_all());
+    assert!(all.contains(Script::Latin));
+    assert_eq!(all.to_string(), "ALL");
+    assert_eq!(all.len(), all.iter().count());
+    let mut set = latin;
+    set.union_with(all);
+    assert!(set.is_all());
+}
+
+#[test]
+fn test_augmented_script_set_base() {
+    use crate::mixed_script::AugmentedScriptSet;
+    use crate::tables::script_list::SCRIPTS;
+    use core::convert::TryFrom;
+    use unicode_script::{Script, ScriptExtension, UnicodeScript};
+
+    assert_eq!(
+        AugmentedScriptSet::for_char('a').base,
+        ScriptExtension::Single(Script::Latin)
+    );
+    assert_eq!(
+        AugmentedScriptSet::default().base,
+        ScriptExtension::Single(Script::Common)
+    );
+    assert_eq!(
+        AugmentedScriptSet::for_str("\u{0660}\u{060C}").base,
+        ScriptExtension::ArabThaa
+    );
+    assert_eq!(
+        AugmentedScriptSet::for_str("a\u{430}").base,
+        ScriptExtension::Single(Script::Unknown)
+    );
+    // The base of an intersection of multi-script characters has the same scripts
+    let set = AugmentedScriptSet::for_str("\u{0964}\u{1CD0}");
+    assert_eq!(AugmentedScriptSet::from(set.base), set);
+    assert!(set.contains(Script::Devanagari) && !set.contains(Script::Tamil));
+
+    let latin = AugmentedScriptSet::for_char('a');
+    let cyrillic = AugmentedScriptSet::for_char('\u{430}');
+    let mut set = latin;
+    set.union_with(AugmentedScriptSet::for_char('\u{363}'));
+    assert_eq!(set.base, ScriptExtension::Single(Script::Latin));
+    // The base of a union takes the value of a set with all the scripts of the other
+    set.union_with(AugmentedScriptSet::from(ScriptExtension::CyrlLatn));
+    assert_eq!(set.base, ScriptExtension::CyrlLatn);
+    // Otherwise it keeps its value, and the set has more scripts
+    let mut set = latin;
+    set.union_with(cyrillic);
+    assert_eq!(set.base, ScriptExtension::Single(Script::Latin));
+    assert!(set.contains(Script::Latin) && set.contains(Script::Cyrillic));
+    assert_eq!(set.len(), 2);
+    let mut other = cyrillic;
+    other.union_with(latin);
+    assert_eq!(other.base, ScriptExtension::Single(Script::Cyrillic));
+    assert_eq!(set, other);
+    assert_eq!(set, AugmentedScriptSet::from(ScriptExtension::CyrlLatn));
+    // Intersections keep the scripts which are not in the base
+    set.intersect_with(cyrillic);
+    assert_eq!(set.base, ScriptExtension::Single(Script::Unknown));
+    assert_eq!(set, cyrillic);
+    assert!(!set.is_empty());
+    // The base can be set directly
+    let mut set = latin;
+    set.base = ScriptExtension::Single(Script::Greek);
+    assert!(set.contains(Script::Greek) && !set.contains(Script::Latin));
+
+    // The generated scripts of each Script_Extensions value agree with unicode-script
+    for c in (0..=0x10FFFF).filter_map(char::from_u32) {
+        let ext = c.script_extension();
+        if Script::try_from(ext).is_ok() {
+            continue;
+        }
+        let set = AugmentedScriptSet::from(ext);
+        for &script in SCRIPTS {
+            assert_eq!(set.contains(script), ext.contains_script(script), "{:?}", c);
+        }
+    }
+}
+
+#[test]
+fn test_reverse_confusables() {
+    use crate::confusable_detection::{
+        confusable_prototype, confusables_of, confusables_of_str, skeleton_eq,
+    };
+    use std::vec::Vec;
+
+    // 'l' is a prototype, 'I' is mapped to it
+    let of_l = confusables_of('l').collect::<Vec<_>>();
+    assert!(of_l.contains(&"I"));
+    assert!(of_l.contains(&"1"));
+    assert!(!of_l.contains(&"l"));
+    let of_capital_i = confusables_of('I').collect::<Vec<_>>();
+    assert_eq!(of_capital_i[0], "l");
+    assert!(!of_capital_i.contains(&"I"));
+    assert_eq!(of_l.len(), of_capital_i.len());
+    for s in of_l {
+        assert!(skeleton_eq(s, "l"));
+    }
+    // Cyrillic 'а' has the same prototype as 'a'
+    assert!(confusables_of('a').any(|s| s == "а"));
+    assert_eq!(confusables_of('ｓ').next(), confusable_prototype('ｓ'));
+
+    assert!(confusables_of_str("m").any(|s| s == "rn"));
+    assert!(confusables_of_str("rn").any(|s| s == "m"));
+    assert!(confusables_of_str("rn").all(|s| s != "rn"));
+    assert_eq!(confusables_of_str("paypal").count(), 0);
+    assert_eq!(confusables_of_str("").count(), 0);
+    // A character which is not confusable with anything
+    assert_eq!(confusables_of('\u{10FFFF}').count(), 0);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_lookalike_generator() {
+    use crate::confusable_detection::skeleton_eq;
+    use crate::lookalike::LookalikeGenerator;
+    use crate::GeneralSecurityProfile;
+    use crate::MixedScript;
+    use std::vec::Vec;
+
+    let found = LookalikeGenerator::new()
+        .max_lookalikes(500)
+        .generate("paypal");
+    assert!(!found.is_empty());
+    assert!(found.len() <= 500);
+    for lookalike in &found {
+        assert_ne!(lookalike.name, "paypal");
+        assert!(skeleton_eq(&lookalike.name, "paypal"));
+        assert!(lookalike.substitutions >= 1 && lookalike.substitutions <= 2);
+        assert_eq!(
+            lookalike.is_single_script(),
+            lookalike.name.is_single_script()
+        );
+        assert!(lookalike.name.chars().all(|c| c.identifier_allowed()));
+    }
+    // Single-script spellings first, each group by number of substitutions
+    let single = found.iter().take_while(|l| l.is_single_script()).count();
+    assert!(single > 0 && single < found.len());
+    assert!(found[single..].iter().all(|l| !l.is_single_script()));
+    for group in [&found[..single], &found[single..]].iter() {
+        assert!(group
+            .windows(2)
+            .all(|w| w[0].substitutions <= w[1].substitutions));
+    }
+    let names = found.iter().map(|l| &*l.name).collect::<Vec<_>>();
+    // Cyrillic 'а'
+    assert!(names.contains(&"pаypal"));
+    // No duplicates
+    let mut sorted = names.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(sorted.len(), names.len());
+
+    // The search stops after visiting the maximum number of partial spellings
+    let found = LookalikeGenerator::new()
+        .allow_restricted(true)
+        .max_substitutions(6)
+        .max_lookalikes(usize::MAX)
+        .max_visits(10_000)
+        .generate("paypalpaypal");
+    assert!(!found.is_empty());
+    assert!(found.len() < 10_000);
+    // The depth of the search is the length of the name, which must not overflow the stack
+    let name = "x".repeat(20_000);
+    let found = LookalikeGenerator::new()
+        .max_visits(100_000)
+        .generate(&name);
+    assert!(!found.is_empty());
+    assert!(found.iter().all(|l| l.name.chars().count() == name.len()));
+    // Mixed-script spellings are still found when most substitutions keep the name's script
+    let found = LookalikeGenerator::new()
+        .max_substitutions(3)
+        .max_lookalikes(usize::MAX)
+        .generate("scope");
+    assert!(found
+        .iter()
+        .any(|l| l.name == "sсope" && !l.is_single_script()));
+
+    // Sequences are substituted too, and whole-script confusables are single-script
+    let found = LookalikeGenerator::new()
+        .max_substitutions(5)
+        .max_lookalikes(10_000)
+        .generate("scope");
+    assert!(found
+        .iter()
+        .any(|l| l.name == "ѕсоре" && l.is_single_script()));
+    let found = LookalikeGenerator::new().generate("burn");
+    assert!(found
+        .iter()
+        .any(|l| l.name == "bum" && l.substitutions == 1));
+
+    assert_eq!(
+        LookalikeGenerator::new()
+            .max_lookalikes(3)
+            .generate("paypal")
+            .len(),
+        3
+    );
+    assert!(LookalikeGenerator::new()
+        .max_substitutions(0)
+        .generate("paypal")
+        .is_empty());
+}
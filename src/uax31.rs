@@ -0,0 +1,250 @@
+//! [Identifier syntax](https://www.unicode.org/reports/tr31/#Default_Identifier_Syntax)
+//! as defined by UAX #31
+
+pub use crate::tables::xid::{is_xid_continue, is_xid_start};
+
+use crate::tables::xid::{
+    is_left_joining, is_letter, is_mn, is_right_joining, is_transparent_joining,
+};
+use unicode_normalization::char::canonical_combining_class;
+
+#[cfg(feature = "alloc")]
+use crate::general_security_profile::IdentifierProfile;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::iter::Peekable;
+#[cfg(feature = "alloc")]
+use core::str::CharIndices;
+
+/// Optional characters which may start (and continue) an identifier,
+/// from [Table 3](https://www.unicode.org/reports/tr31/#Table_Optional_Start) of UAX #31
+pub const OPTIONAL_START: &[char] = &['\u{24}', '\u{5f}'];
+
+/// Optional characters which may occur in the middle of an identifier,
+/// from [Table 3a](https://www.unicode.org/reports/tr31/#Table_Optional_Medial) of UAX #31
+///
+/// A medial character which is not XID_Continue may not start or end an identifier,
+/// and may not follow another such medial character. U+200C ZERO WIDTH NON-JOINER
+/// is only allowed in the contexts checked by [`is_joiner_allowed`].
+pub const OPTIONAL_MEDIAL: &[char] = &[
+    '\u{27}', '\u{2d}', '\u{2e}', '\u{3a}', '\u{b7}', '\u{58a}', '\u{5f4}', '\u{f0b}', '\u{200c}',
+    '\u{2010}', '\u{2019}', '\u{2027}', '\u{30a0}', '\u{30fb}',
+];
+
+/// Check if a character may start an identifier, i.e. is XID_Start or one of [`OPTIONAL_START`]
+pub fn is_identifier_start(c: char) -> bool {
+    is_xid_start(c) || OPTIONAL_START.contains(&c)
+}
+
+/// Check if a character may continue an identifier, i.e. is XID_Continue or one of [`OPTIONAL_START`]
+pub fn is_identifier_continue(c: char) -> bool {
+    is_xid_continue(c) || OPTIONAL_START.contains(&c)
+}
+
+/// Check if a character is one of the [`OPTIONAL_MEDIAL`] characters
+pub fn is_identifier_medial(c: char) -> bool {
+    OPTIONAL_MEDIAL.contains(&c)
+}
+
+/// U+200C ZERO WIDTH NON-JOINER
+const ZWNJ: char = '\u{200c}';
+
+/// U+200D ZERO WIDTH JOINER
+const ZWJ: char = '\u{200d}';
+
+/// Canonical_Combining_Class=Virama
+const VIRAMA: u8 = 9;
+
+/// Check if the ZWJ or ZWNJ at byte offset `index` of a string is in one of the contexts
+/// allowed by [UAX #31](https://www.unicode.org/reports/tr31/#Layout_and_Format_Control_Characters)
+///
+/// A ZWNJ may break a cursive connection (rule A1), and either character may follow
+/// a virama after a letter, to request a conjunct form (rules A2 and B).
+///
+/// Returns false if there is no ZWJ or ZWNJ at that offset.
+pub fn is_joiner_allowed(s: &str, index: usize) -> bool {
+    let joiner = match s.get(index..).and_then(|rest| rest.chars().next()) {
+        Some(ch) if ch == ZWNJ || ch == ZWJ => ch,
+        _ => return false,
+    };
+    let (before, after) = (&s[..index], &s[index + joiner.len_utf8()..]);
+    (joiner == ZWNJ && in_cursive_context(before, after)) || in_conjunct_context(before)
+}
+
+/// `$LJ $T* ZWNJ $T* $RJ`
+fn in_cursive_context(before: &str, after: &str) -> bool {
+    let left = before.chars().rev().find(|&c| !is_transparent_joining(c));
+    let right = after.chars().find(|&c| !is_transparent_joining(c));
+    matches!(left, Some(c) if is_left_joining(c)) && matches!(right, Some(c) if is_right_joining(c))
+}
+
+/// `$L $M* $V $M₁* (ZWNJ | ZWJ)`, where `$M₁` are the nonspacing marks which
+/// have a non-zero combining class
+fn in_conjunct_context(before: &str) -> bool {
+    let mut chars = before.chars().rev();
+    while let Some(c) = chars.next() {
+        let class = canonical_combining_class(c);
+        if class == VIRAMA {
+            let mut rest = chars.clone().skip_while(|&c| is_mn(c));
+            if matches!(rest.next(), Some(c) if is_letter(c)) {
+                return true;
+            }
+        }
+        // Anything but a virama or a mark of `$M₁` breaks the context
+        if !is_mn(c) || class == 0 {
+            return false;
+        }
+    }
+    false
+}
+
+/// A problem found by `validate_identifier` or `identifier_errors`, which need the `alloc` feature
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub enum IdentifierError {
+    /// The identifier is empty
+    Empty,
+    /// The first character can not start an identifier
+    InvalidStart {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// A character can not occur in an identifier
+    InvalidContinue {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// A medial character starts or ends the identifier, or follows another medial character
+    MisplacedMedial {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// A ZWJ or ZWNJ is not in a context [allowed](is_joiner_allowed) by UAX #31
+    MisplacedJoiner {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+    /// A character is not allowed by the identifier profile
+    NotAllowed {
+        /// Byte offset of the character
+        index: usize,
+        /// The character
+        ch: char,
+    },
+}
+
+/// Find the syntax errors and identifier profile violations of an identifier,
+/// in the order of the characters they are about
+///
+/// The syntax is the UAX #31 default identifier syntax extended with the
+/// [`OPTIONAL_START`] and [`OPTIONAL_MEDIAL`] characters, and with ZWJ and ZWNJ
+/// in the contexts checked by [`is_joiner_allowed`]. Since these characters are
+/// then checked against the profile too, the profile decides which of them are usable:
+/// for instance `-` is allowed by the General Security Profile but `$` is not.
+#[cfg(feature = "alloc")]
+pub fn identifier_errors<'a>(s: &'a str, profile: &'a IdentifierProfile) -> IdentifierErrors<'a> {
+    IdentifierErrors {
+        s,
+        profile,
+        chars: s.char_indices().peekable(),
+        empty: s.is_empty(),
+        prev_medial: false,
+        pending: None,
+    }
+}
+
+/// Check that a string is a well-formed identifier made of characters allowed by the profile,
+/// returning all the problems found otherwise
+///
+/// See [`identifier_errors`] for the syntax used.
+#[cfg(feature = "alloc")]
+pub fn validate_identifier(
+    s: &str,
+    profile: &IdentifierProfile,
+) -> Result<(), Vec<IdentifierError>> {
+    let errors: Vec<_> = identifier_errors(s, profile).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// An iterator over the problems of an identifier.
+///
+/// This is created by [`identifier_errors`].
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub struct IdentifierErrors<'a> {
+    s: &'a str,
+    profile: &'a IdentifierProfile,
+    chars: Peekable<CharIndices<'a>>,
+    empty: bool,
+    prev_medial: bool,
+    /// A profile violation found along with a syntax error on the same character
+    pending: Option<IdentifierError>,
+}
+
+#[cfg(feature = "alloc")]
+impl Iterator for IdentifierErrors<'_> {
+    type Item = IdentifierError;
+
+    fn next(&mut self) -> Option<IdentifierError> {
+        if let Some(error) = self.pending.take() {
+            return Some(error);
+        }
+        if self.empty {
+            self.empty = false;
+            return Some(IdentifierError::Empty);
+        }
+        while let Some((index, ch)) = self.chars.next() {
+            let syntax_error = if ch == ZWNJ || ch == ZWJ {
+                self.prev_medial = false;
+                if is_joiner_allowed(self.s, index) {
+                    None
+                } else {
+                    Some(IdentifierError::MisplacedJoiner { index, ch })
+                }
+            } else if is_identifier_medial(ch) && !is_xid_continue(ch) {
+                let misplaced = index == 0 || self.prev_medial || self.chars.peek().is_none();
+                self.prev_medial = true;
+                if misplaced {
+                    Some(IdentifierError::MisplacedMedial { index, ch })
+                } else {
+                    None
+                }
+            } else {
+                self.prev_medial = false;
+                if index == 0 && !is_identifier_start(ch) {
+                    Some(IdentifierError::InvalidStart { index, ch })
+                } else if !is_identifier_continue(ch) {
+                    Some(IdentifierError::InvalidContinue { index, ch })
+                } else {
+                    None
+                }
+            };
+            let profile_error = if self.profile.allows(ch) {
+                None
+            } else {
+                Some(IdentifierError::NotAllowed { index, ch })
+            };
+            match (syntax_error, profile_error) {
+                (Some(error), pending) => {
+                    self.pending = pending;
+                    return Some(error);
+                }
+                (None, Some(error)) => return Some(error),
+                (None, None) => {}
+            }
+        }
+        None
+    }
+}